@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/bin/05.rs"]
+mod solution;
+
+fuzz_target!(|input: &str| {
+    let _ = solution::part_one(input);
+    let _ = solution::part_two(input);
+});
@@ -1,4 +1,6 @@
-advent_of_code::solution!(2);
+use std::fmt::{self, Display, Formatter, Write};
+
+advent_of_code::solution!(2, explain = explain);
 
 #[derive(Debug)]
 struct Report {
@@ -24,89 +26,145 @@ impl Report {
     }
 
     fn check_safe(&self) -> bool {
-        let mut is_ascending: Option<bool> = None;
-        for nums in self.levels.windows(2) {
-            if !Report::has_valid_ordering(nums[0], nums[1], &mut is_ascending) {
-                return false;
-            };
-        }
-        true
+        self.first_violation().is_none()
     }
 
     fn brute_check_safe_with_removal(&self) -> bool {
         if self.check_safe() {
             return true;
         }
-        self.levels.iter().enumerate().any(|(i, _)| {
-            let mut is_ascending: Option<bool> = None;
+        (0..self.levels.len()).any(|i| {
             let mut subvec = self.levels.clone();
             subvec.remove(i);
-            for nums in subvec.windows(2) {
-                if !Report::has_valid_ordering(nums[0], nums[1], &mut is_ascending) {
-                    return false;
-                }
-            }
-            true
+            Report::from_vec(subvec).check_safe()
         })
     }
 
     fn check_safe_with_removal(&self) -> bool {
-        let mut is_ascending: Option<bool> = None;
-        let mut removed_val: Option<u32> = None;
-
-        // check if removal or last or first element is safe
-        if Report::from_vec(self.levels.split_first().unwrap().1.to_vec()).check_safe()
-            || Report::from_vec(self.levels.split_last().unwrap().1.to_vec()).check_safe()
-        {
-            return true;
-        }
+        !matches!(self.diagnose(), Diagnosis::Unsafe { .. })
+    }
 
-        // check internal elements
-        let mut window_iter = self.levels.windows(3);
-        while let Some(nums) = window_iter.next() {
-            if Report::has_valid_ordering(nums[0], nums[1], &mut is_ascending) {
-                continue;
-            } else if Report::has_valid_ordering(nums[0], nums[2], &mut is_ascending)
-                && removed_val.is_none()
-            {
-                removed_val = Some(nums[1]);
-                // skip next
-                let _ = window_iter.next();
-            } else {
-                return false;
+    /// The first step that makes the report unsafe, reading it in the direction of its first step.
+    fn first_violation(&self) -> Option<(usize, Violation)> {
+        let mut is_ascending: Option<bool> = None;
+        for (step, nums) in self.levels.windows(2).enumerate() {
+            let violation = match nums[0].abs_diff(nums[1]) {
+                0 => Some(Violation::Unchanged),
+                4.. => Some(Violation::StepTooLarge),
+                _ => match is_ascending {
+                    Some(is_asc) if is_asc != (nums[0] < nums[1]) => {
+                        Some(Violation::DirectionChange)
+                    }
+                    _ => {
+                        is_ascending = Some(nums[0] < nums[1]);
+                        None
+                    }
+                },
+            };
+            if let Some(violation) = violation {
+                return Some((step, violation));
             }
         }
+        None
+    }
 
-        // because window size is 3, check last two elements for correct ordering
-        let count = self.levels.len();
-        if let Some(removed) = removed_val {
-            if *self.levels.get(count - 2).unwrap() == removed {
-                return Report::has_valid_ordering(
-                    *self.levels.get(count - 3).unwrap(),
-                    *self.levels.get(count - 1).unwrap(),
-                    &mut is_ascending,
-                );
-            }
+    /// Linear-time variant of `brute_check_safe_with_removal` that also reports why the report is unsafe,
+    /// and which level to remove. For each direction, only removing one of the levels of the first invalid
+    /// step can make the report safe.
+    fn diagnose(&self) -> Diagnosis {
+        let Some((step, violation)) = self.first_violation() else {
+            return Diagnosis::Safe;
+        };
+
+        let removable = [true, false].into_iter().find_map(|ascending| {
+            let first_invalid = self
+                .levels
+                .windows(2)
+                .position(|nums| !Report::is_valid_step(nums[0], nums[1], ascending))?;
+
+            [first_invalid, first_invalid + 1]
+                .into_iter()
+                .find(|&removed| self.is_safe_without(removed, ascending))
+        });
+
+        match removable {
+            Some(removed) => Diagnosis::Tolerable {
+                step,
+                violation,
+                removed,
+            },
+            None => Diagnosis::Unsafe { step, violation },
         }
-        Report::has_valid_ordering(
-            *self.levels.get(count - 2).unwrap(),
-            *self.levels.get(count - 1).unwrap(),
-            &mut is_ascending,
-        )
     }
 
-    fn has_valid_ordering(a: u32, b: u32, is_asc: &mut Option<bool>) -> bool {
-        if !Report::is_diff_gradual(a, b) {
-            return false;
-        }
-        if let Some(is_asc) = is_asc {
-            if *is_asc != (a < b) {
-                return false;
-            }
-        } else {
-            *is_asc = Some(a < b);
+    /// Indices of the fewest levels to remove to make the report safe, if that takes at most `max_removals`.
+    /// Keeps the longest chain of valid steps, where a step may skip at most `max_removals` levels,
+    /// so this takes O(n * `max_removals`) time.
+    fn removals_to_make_safe(&self, max_removals: usize) -> Option<Vec<usize>> {
+        let n = self.levels.len();
+        if n == 0 {
+            return Some(vec![]);
         }
-        true
+
+        [true, false]
+            .into_iter()
+            .filter_map(|ascending| {
+                // removals[j]: fewest removals before level j for a valid chain ending at j.
+                let mut removals = vec![0; n];
+                let mut previous = vec![None; n];
+
+                for j in 0..n {
+                    removals[j] = j;
+                    for i in j.saturating_sub(max_removals + 1)..j {
+                        if !Report::is_valid_step(self.levels[i], self.levels[j], ascending) {
+                            continue;
+                        }
+                        let total = removals[i] + (j - i - 1);
+                        if total < removals[j] {
+                            removals[j] = total;
+                            previous[j] = Some(i);
+                        }
+                    }
+                }
+
+                let (end, total) = (0..n)
+                    .map(|j| (j, removals[j] + (n - 1 - j)))
+                    .min_by_key(|&(_, total)| total)?;
+                if total > max_removals {
+                    return None;
+                }
+
+                let mut kept = vec![false; n];
+                let mut level = Some(end);
+                while let Some(i) = level {
+                    kept[i] = true;
+                    level = previous[i];
+                }
+                Some((0..n).filter(|&i| !kept[i]).collect::<Vec<_>>())
+            })
+            .min_by_key(Vec::len)
+    }
+
+    fn is_safe_without(&self, removed: usize, ascending: bool) -> bool {
+        let mut levels = self
+            .levels
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != removed)
+            .map(|(_, level)| *level);
+
+        let Some(mut previous) = levels.next() else {
+            return true;
+        };
+        levels.all(|level| {
+            let valid = Report::is_valid_step(previous, level, ascending);
+            previous = level;
+            valid
+        })
+    }
+
+    fn is_valid_step(a: u32, b: u32, ascending: bool) -> bool {
+        Report::is_diff_gradual(a, b) && (a < b) == ascending
     }
 
     fn is_diff_gradual(a: u32, b: u32) -> bool {
@@ -115,25 +173,117 @@ impl Report {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let reports: Vec<Report> = input
+/// Why a report is unsafe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Violation {
+    Unchanged,
+    StepTooLarge,
+    DirectionChange,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Violation::Unchanged => "level unchanged",
+            Violation::StepTooLarge => "step too large",
+            Violation::DirectionChange => "direction change",
+        })
+    }
+}
+
+/// Result of validating a report. `step` is the index of the first level of the first invalid step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Diagnosis {
+    Safe,
+    /// Safe after removing the level at index `removed`.
+    Tolerable {
+        step: usize,
+        violation: Violation,
+        removed: usize,
+    },
+    Unsafe {
+        step: usize,
+        violation: Violation,
+    },
+}
+
+fn parse(input: &str) -> Vec<Report> {
+    input
         .split('\n')
         .filter(|line| !line.is_empty())
         .map(Report::from_str)
-        .collect();
+        .collect()
+}
+
+/// Lists the diagnosis of each report, with levels numbered from 1. Unsafe reports also list the
+/// fewest levels to remove to make them safe. Unlike the diagnosis, which is linear, that search
+/// allows any number of removals and takes O(n²) time per unsafe report.
+pub fn explain(input: &str) -> String {
+    let mut output = String::new();
+
+    for (line, report) in parse(input).iter().enumerate() {
+        let levels = &report.levels;
+        let describe = |step: usize, violation: Violation| {
+            format!(
+                "{violation} from level {} to {} ({} -> {})",
+                step + 1,
+                step + 2,
+                levels[step],
+                levels[step + 1]
+            )
+        };
+
+        let diagnosis = match report.diagnose() {
+            Diagnosis::Safe => "safe".to_string(),
+            Diagnosis::Tolerable {
+                step,
+                violation,
+                removed,
+            } => format!(
+                "safe without level {} ({}), {}",
+                removed + 1,
+                levels[removed],
+                describe(step, violation)
+            ),
+            Diagnosis::Unsafe { step, violation } => {
+                let removals = report
+                    .removals_to_make_safe(levels.len())
+                    .unwrap_or_default();
+                format!(
+                    "unsafe, {}; safe without {} levels ({})",
+                    describe(step, violation),
+                    removals.len(),
+                    removals
+                        .iter()
+                        .map(|i| (i + 1).to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+        };
+
+        let levels = levels
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(output, "{:>4}: {levels}: {diagnosis}", line + 1).unwrap();
+    }
+
+    output
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    let reports = parse(input);
     Some(reports.iter().filter(|r| r.check_safe()).count() as u32)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let reports: Vec<Report> = input
-        .split('\n')
-        .filter(|line| !line.is_empty())
-        .map(Report::from_str)
-        .collect();
+    let reports = parse(input);
     Some(
         reports
             .iter()
-            .filter(|r| r.brute_check_safe_with_removal())
+            .filter(|r| r.check_safe_with_removal())
             .count() as u32,
     )
 }
@@ -141,6 +291,8 @@ pub fn part_two(input: &str) -> Option<u32> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::template::differential::{shrink, Differential};
+    use advent_of_code::template::rng::Rng;
 
     #[test]
     fn test_part_one() {
@@ -153,4 +305,134 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(9));
     }
+
+    fn levels(rng: &mut Rng) -> Vec<u32> {
+        // levels close together, so that many reports are nearly safe.
+        let mut level = rng.range(1..=20);
+        (0..rng.below(9))
+            .map(|_| {
+                level = (level + rng.range(-4..=4)).max(1);
+                level as u32
+            })
+            .collect()
+    }
+
+    fn format(levels: &[u32]) -> String {
+        levels
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn without(levels: &[u32], removed: &[usize]) -> Report {
+        Report::from_vec(
+            levels
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !removed.contains(i))
+                .map(|(_, level)| *level)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn diagnoses_reports() {
+        let diagnose = |line: &str| Report::from_str(line).diagnose();
+
+        assert_eq!(diagnose("7 6 4 2 1"), Diagnosis::Safe);
+        assert_eq!(
+            diagnose("1 2 7 8 9"),
+            Diagnosis::Unsafe {
+                step: 1,
+                violation: Violation::StepTooLarge
+            }
+        );
+        assert_eq!(
+            diagnose("1 3 2 4 5"),
+            Diagnosis::Tolerable {
+                step: 1,
+                violation: Violation::DirectionChange,
+                removed: 1
+            }
+        );
+        assert_eq!(
+            diagnose("8 6 4 4 1"),
+            Diagnosis::Tolerable {
+                step: 2,
+                violation: Violation::Unchanged,
+                removed: 2
+            }
+        );
+    }
+
+    #[test]
+    fn explains_reports() {
+        let explanation = explain("1 3 2 4 5\n9 7 6 2 1\n");
+        let lines: Vec<&str> = explanation.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "   1: 1 3 2 4 5: safe without level 2 (3), direction change from level 2 to 3 (3 -> 2)",
+                "   2: 9 7 6 2 1: unsafe, step too large from level 3 to 4 (6 -> 2); safe without 2 levels (4, 5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn linear_removal_check_matches_brute_force() {
+        Differential::new(
+            levels,
+            |levels| Report::from_vec(levels.clone()).brute_check_safe_with_removal(),
+            |levels| {
+                // the removed level has to make the report safe.
+                match Report::from_vec(levels.clone()).diagnose() {
+                    Diagnosis::Safe => true,
+                    Diagnosis::Tolerable { removed, .. } => {
+                        without(levels, &[removed]).check_safe()
+                    }
+                    Diagnosis::Unsafe { .. } => false,
+                }
+            },
+        )
+        .shrink(|levels| shrink::vec_with(levels, |&n| shrink::number(n)))
+        .format(|levels| format(levels))
+        .cases(10_000)
+        .assert();
+    }
+
+    #[test]
+    fn removals_match_brute_force() {
+        Differential::new(
+            |rng| (levels(rng), rng.below(4)),
+            |(levels, max_removals)| {
+                // fewest removals over all subsets of levels.
+                (0_u32..1 << levels.len())
+                    .filter(|mask| mask.count_ones() as usize <= *max_removals)
+                    .filter(|mask| {
+                        let removed: Vec<usize> =
+                            (0..levels.len()).filter(|i| mask & (1 << i) != 0).collect();
+                        without(levels, &removed).check_safe()
+                    })
+                    .map(u32::count_ones)
+                    .min()
+            },
+            |(levels, max_removals)| {
+                let removed =
+                    Report::from_vec(levels.clone()).removals_to_make_safe(*max_removals)?;
+                // report a wrong count if the removals do not make the report safe.
+                let safe = without(levels, &removed).check_safe();
+                Some(if safe { removed.len() as u32 } else { u32::MAX })
+            },
+        )
+        .shrink(|(levels, max_removals)| {
+            shrink::vec_with(levels, |&n| shrink::number(n))
+                .into_iter()
+                .map(|levels| (levels, *max_removals))
+                .collect()
+        })
+        .format(|(levels, max_removals)| format!("{} (at most {max_removals})", format(levels)))
+        .cases(10_000)
+        .assert();
+    }
 }
@@ -63,7 +63,7 @@ impl WordSearch {
     }
 
     fn find_word_x_count(&self, word: &str) -> u32 {
-        assert!(word.len() % 2 != 0);
+        assert!(!word.len().is_multiple_of(2));
         let word_dist = (word.len() - 1) as i16;
         let mut count_found: u32 = 0;
         for sy in 0..self.height {
@@ -1,10 +1,15 @@
 use rayon::iter::ParallelIterator;
-use std::{collections::HashSet, fmt::Display, hash::BuildHasherDefault, ops::Add};
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+    hash::BuildHasherDefault,
+    ops::Add,
+};
 
-use fxhash::FxHasher;
-use rayon::iter::IntoParallelRefIterator;
+use fxhash::{FxHashSet, FxHasher};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator};
 
-advent_of_code::solution!(6);
+advent_of_code::solution!(6, explain = explain);
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 struct Pos {
@@ -141,6 +146,7 @@ impl PatrolMap {
         }
     }
 
+    #[cfg(test)]
     fn is_patrol_loop_with_obstacle(&mut self, obstacle_pos: Pos, print: bool) -> Result<bool, ()> {
         if *self.get_tile(obstacle_pos).ok_or(())? == Tile::Wall {
             return Err(());
@@ -195,6 +201,299 @@ impl Display for PatrolMap {
     }
 }
 
+/// Directions in the order the guard turns to, starting with up.
+const DIRS: [Dir; 4] = [
+    Dir { x: 0, y: -1 },
+    Dir { x: 1, y: 0 },
+    Dir { x: 0, y: 1 },
+    Dir { x: -1, y: 0 },
+];
+
+type Cell = usize;
+type DirIndex = usize;
+
+/// The state of the guard after moving until the next wall: where it stopped, and the direction it turned to.
+type Turn = (Cell, DirIndex);
+
+/// The patrol map with precomputed jumps, so that the guard moves from wall to wall in O(1).
+/// A single added obstacle is handled by cutting jumps short, without changing the tables.
+struct JumpMap {
+    width: usize,
+    height: usize,
+    walls: Vec<bool>,
+    start: Cell,
+    /// `jumps[cell][dir]` is the cell the guard stops at when walking in `dir` from `cell`,
+    /// right before the next wall, or `None` if it leaves the map.
+    jumps: Vec<[Option<Cell>; 4]>,
+}
+
+impl JumpMap {
+    fn new(patrol: &PatrolMap) -> Self {
+        let height = patrol.map.len();
+        let width = patrol.map[0].len();
+        let walls: Vec<bool> = patrol
+            .map
+            .iter()
+            .flatten()
+            .map(|tile| *tile == Tile::Wall)
+            .collect();
+
+        let mut jumps = vec![[None; 4]; width * height];
+        for (dir, step) in DIRS.iter().enumerate() {
+            let vertical = step.x == 0;
+            let (lines, len) = if vertical {
+                (width, height)
+            } else {
+                (height, width)
+            };
+
+            for line in 0..lines {
+                // walk each row or column against `dir`, so the next wall in `dir` is already known.
+                let mut stop = None;
+                let mut after_wall = false;
+                for k in 0..len {
+                    let k = if step.x + step.y > 0 { len - 1 - k } else { k };
+                    let cell = if vertical {
+                        k * width + line
+                    } else {
+                        line * width + k
+                    };
+
+                    if walls[cell] {
+                        after_wall = true;
+                        continue;
+                    }
+                    if after_wall {
+                        stop = Some(cell);
+                        after_wall = false;
+                    }
+                    jumps[cell][dir] = stop;
+                }
+            }
+        }
+
+        let start = patrol.init_guard_pos;
+        JumpMap {
+            width,
+            height,
+            walls,
+            start: start.y as usize * width + start.x as usize,
+            jumps,
+        }
+    }
+
+    fn pos(&self, cell: Cell) -> Pos {
+        Pos {
+            x: (cell % self.width) as i16,
+            y: (cell / self.width) as i16,
+        }
+    }
+
+    fn cell(&self, pos: Pos) -> Option<Cell> {
+        let (x, y) = (usize::try_from(pos.x).ok()?, usize::try_from(pos.y).ok()?);
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    /// Moves the guard from `cell` in `dir` until the next wall or `obstacle`, returning where it stops.
+    fn jump(&self, cell: Cell, dir: DirIndex, obstacle: Option<Cell>) -> Option<Cell> {
+        let stop = self.jumps[cell][dir];
+        let Some(obstacle) = obstacle else {
+            return stop;
+        };
+
+        // distance along `dir` from `cell`, if `other` is ahead on the same row or column.
+        let ahead = |other: Cell| {
+            let (from, to, step) = (self.pos(cell), self.pos(other), DIRS[dir]);
+            let (dx, dy) = (to.x - from.x, to.y - from.y);
+            let distance = dx * step.x + dy * step.y;
+            (dx * step.y == dy * step.x && distance > 0).then_some(distance)
+        };
+
+        match (ahead(obstacle), stop.map(|stop| ahead(stop).unwrap_or(0))) {
+            // the obstacle is ahead of the wall (or the edge), so the guard stops right before it.
+            (Some(to_obstacle), to_stop)
+                if to_stop.is_none_or(|to_stop| to_obstacle <= to_stop) =>
+            {
+                self.cell(
+                    self.pos(obstacle)
+                        + Dir {
+                            x: -DIRS[dir].x,
+                            y: -DIRS[dir].y,
+                        },
+                )
+            }
+            _ => stop,
+        }
+    }
+
+    /// Follows the guard from `turn` with an added `obstacle`, returning its turns until one repeats,
+    /// and the index of the first turn of the loop. Returns `None` if the guard leaves the map.
+    fn walk(&self, turn: Turn, obstacle: Option<Cell>) -> Option<(Vec<Turn>, usize)> {
+        let mut seen = FxHashSet::default();
+        let mut turns = vec![];
+        let (mut cell, mut dir) = turn;
+
+        loop {
+            cell = self.jump(cell, dir, obstacle)?;
+            dir = (dir + 1) % 4;
+            if !seen.insert((cell, dir)) {
+                let start = turns.iter().position(|&turn| turn == (cell, dir)).unwrap();
+                return Some((turns, start));
+            }
+            turns.push((cell, dir));
+        }
+    }
+
+    /// The cells on the guard's original route except the start, each with the cell and direction
+    /// the guard first entered it from.
+    fn route(&self) -> Vec<(Cell, Turn)> {
+        let mut entered = vec![false; self.walls.len()];
+        entered[self.start] = true;
+        // the directions each cell was left in, in case the guard loops without an added obstacle.
+        let mut left = vec![0_u8; self.walls.len()];
+        let mut route = vec![];
+        let (mut cell, mut dir) = (self.start, 0);
+
+        while let Some(next) = self.cell(self.pos(cell) + DIRS[dir]) {
+            if left[cell] & (1 << dir) != 0 {
+                break;
+            }
+            left[cell] |= 1 << dir;
+            if self.walls[next] {
+                dir = (dir + 1) % 4;
+                continue;
+            }
+            if !entered[next] {
+                entered[next] = true;
+                route.push((next, (cell, dir)));
+            }
+            cell = next;
+        }
+
+        route
+    }
+
+    /// Obstacle cells that make the guard loop. The guard walks its original route up to the obstacle,
+    /// so each walk starts right before the guard first runs into it.
+    fn loop_obstacles(&self) -> Vec<Cell> {
+        self.route()
+            .into_par_iter()
+            .filter(|&(obstacle, turn)| self.walk(turn, Some(obstacle)).is_some())
+            .map(|(obstacle, _)| obstacle)
+            .collect()
+    }
+
+    /// The cells the guard turns at in the loop caused by an obstacle at `obstacle`, in order.
+    /// Returns `None` if the guard does not loop, or the obstacle cannot be placed there.
+    fn loop_path(&self, obstacle: Pos) -> Option<Vec<Pos>> {
+        let obstacle = self.cell(obstacle)?;
+        if self.walls[obstacle] || obstacle == self.start {
+            return None;
+        }
+
+        // the guard starts facing up, as if it had just turned there.
+        let (turns, start) = self.walk((self.start, 0), Some(obstacle))?;
+        Some(
+            turns[start..]
+                .iter()
+                .map(|&(cell, _)| self.pos(cell))
+                .collect(),
+        )
+    }
+
+    /// Draws the map with an obstacle `O` and the loop through `corners` like the puzzle does.
+    fn render_loop(&self, obstacle: Pos, corners: &[Pos]) -> String {
+        let mut grid: Vec<Vec<char>> = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        if self.walls[y * self.width + x] {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (i, from) in corners.iter().enumerate() {
+            let to = corners[(i + 1) % corners.len()];
+            let (step, line) = if from.x == to.x {
+                (
+                    Dir {
+                        x: 0,
+                        y: (to.y - from.y).signum(),
+                    },
+                    '|',
+                )
+            } else {
+                (
+                    Dir {
+                        x: (to.x - from.x).signum(),
+                        y: 0,
+                    },
+                    '-',
+                )
+            };
+
+            let mut pos = *from;
+            loop {
+                let c = &mut grid[pos.y as usize][pos.x as usize];
+                *c = match *c {
+                    '.' if pos != *from && pos != to => line,
+                    '.' | '+' => '+',
+                    '|' | '-' if *c != line => '+',
+                    other => other,
+                };
+                if pos == to {
+                    break;
+                }
+                pos = pos + step;
+            }
+        }
+
+        let start = self.pos(self.start);
+        grid[start.y as usize][start.x as usize] = '^';
+        grid[obstacle.y as usize][obstacle.x as usize] = 'O';
+        grid.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Maps up to this size have the loops drawn by `--explain`.
+const MAX_RENDER_SIZE: usize = 40;
+
+/// Lists the obstacles that make the guard loop, drawing the loops on small maps.
+pub fn explain(input: &str) -> String {
+    let jumps = JumpMap::new(&PatrolMap::from_str(input));
+    let mut obstacles: Vec<Pos> = jumps
+        .loop_obstacles()
+        .into_iter()
+        .map(|cell| jumps.pos(cell))
+        .collect();
+    obstacles.sort_unstable_by_key(|pos| (pos.y, pos.x));
+
+    let mut output = String::new();
+    for obstacle in obstacles {
+        let corners = jumps.loop_path(obstacle).unwrap();
+        writeln!(
+            output,
+            "obstacle at {},{}: loop with {} turns",
+            obstacle.x,
+            obstacle.y,
+            corners.len()
+        )
+        .unwrap();
+        if jumps.width.max(jumps.height) <= MAX_RENDER_SIZE {
+            writeln!(output, "{}\n", jumps.render_loop(obstacle, &corners)).unwrap();
+        }
+    }
+    output
+}
+
 pub fn part_one(input: &str) -> Option<u32> {
     let mut patrol = PatrolMap::from_str(input);
     let _ = patrol.is_patrol_loop(false);
@@ -209,6 +508,14 @@ pub fn part_one(input: &str) -> Option<u32> {
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
+    let jumps = JumpMap::new(&PatrolMap::from_str(input));
+    Some(jumps.loop_obstacles().len() as u32)
+}
+
+/// The original part two, walking cell by cell and cloning the map for each obstacle. Used to
+/// validate the jumps on inputs where it follows the puzzle rules, see `tests::original_applies`.
+#[cfg(test)]
+fn brute_part_two(input: &str) -> Option<u32> {
     let mut patrol = PatrolMap::from_str(input);
     let _ = patrol.is_patrol_loop(false);
     let candidates = patrol.visited.clone();
@@ -234,6 +541,8 @@ pub fn part_two(input: &str) -> Option<u32> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::gen;
+    use advent_of_code::template::differential::Differential;
 
     #[test]
     fn test_part_one() {
@@ -246,4 +555,107 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
+
+    #[test]
+    fn renders_loop_paths() {
+        let jumps = JumpMap::new(&PatrolMap::from_str(&advent_of_code::template::read_file(
+            "examples", DAY,
+        )));
+        let obstacle = Pos { x: 3, y: 6 };
+        let corners = jumps.loop_path(obstacle).unwrap();
+        assert_eq!(corners.len(), 4);
+        assert_eq!(
+            jumps.render_loop(obstacle, &corners),
+            "....#.....\n\
+             ....+---+#\n\
+             ....|...|.\n\
+             ..#.|...|.\n\
+             ....|..#|.\n\
+             ....|...|.\n\
+             .#.O^---+.\n\
+             ........#.\n\
+             #.........\n\
+             ......#..."
+        );
+        assert_eq!(jumps.loop_path(Pos { x: 0, y: 0 }), None);
+        assert_eq!(jumps.loop_path(Pos { x: 4, y: 0 }), None);
+    }
+
+    #[test]
+    fn keeps_the_start_free() {
+        // the guard walks back down over its start, where an obstacle would make it loop.
+        let input = "..#.\n...#\n.#..\n.#^.\n";
+        assert!(!original_applies(input));
+        assert_eq!(brute_part_two(input), Some(1));
+        assert_eq!(part_two(input), Some(0));
+    }
+
+    /// Whether the original solution follows the puzzle rules on `input`. It also counts an obstacle
+    /// on the guard's start, which only matters if the guard walks back onto it, and it turns forever
+    /// if an obstacle walls the guard in at the start.
+    fn original_applies(input: &str) -> bool {
+        let jumps = JumpMap::new(&PatrolMap::from_str(input));
+        let start = jumps.pos(jumps.start);
+        let walls_around = DIRS
+            .iter()
+            .filter_map(|dir| jumps.cell(start + *dir))
+            .filter(|cell| jumps.walls[*cell])
+            .count();
+        if walls_around > 2 {
+            return false;
+        }
+
+        // walk the original route, like `JumpMap::route`, looking for the start.
+        let mut left = vec![0_u8; jumps.walls.len()];
+        let (mut cell, mut dir) = (jumps.start, 0);
+        while let Some(next) = jumps.cell(jumps.pos(cell) + DIRS[dir]) {
+            if left[cell] & (1 << dir) != 0 {
+                break;
+            }
+            left[cell] |= 1 << dir;
+            if jumps.walls[next] {
+                dir = (dir + 1) % 4;
+                continue;
+            }
+            if next == jumps.start {
+                return false;
+            }
+            cell = next;
+        }
+        true
+    }
+
+    #[test]
+    fn jumps_match_walking() {
+        Differential::new(
+            |rng| loop {
+                let size = rng.below(12) + 1;
+                let input = gen::day06(rng, size);
+                if original_applies(&input) {
+                    break input;
+                }
+            },
+            |input| (part_one(input), brute_part_two(input)),
+            |input| {
+                // the route does not include the start.
+                let jumps = JumpMap::new(&PatrolMap::from_str(input));
+                (Some(jumps.route().len() as u32 + 1), part_two(input))
+            },
+        )
+        .shrink(|input| {
+            // remove walls, one at a time.
+            input
+                .match_indices('#')
+                .map(|(i, _)| {
+                    let mut smaller = input.clone();
+                    smaller.replace_range(i..=i, ".");
+                    smaller
+                })
+                .filter(|smaller| original_applies(smaller))
+                .collect()
+        })
+        .format(Clone::clone)
+        .cases(2_000)
+        .assert();
+    }
 }
@@ -1,158 +1,381 @@
-use itertools::{repeat_n, Itertools};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::{self, Display, Formatter, Write},
+};
 
-advent_of_code::solution!(9);
+advent_of_code::solution!(9, explain = explain);
 
 fn char_to_digit(c: char) -> usize {
     c.to_digit(10)
         .unwrap_or_else(|| panic!("{} couldn't be converted to base 10 digit", c)) as usize
 }
 
-#[allow(dead_code)]
-fn print_blocks(blocks: &[Option<usize>]) {
-    println!(
-        "{}",
-        blocks
-            .iter()
-            .map(|block| match block {
-                Some(val) => val.to_string(),
-                None => ".".to_owned(),
-            })
-            .join("")
-    );
-}
-
 type FileId = usize;
 type Index = usize;
-type FileSize = usize;
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let mut blocks: Vec<Option<FileId>> = Vec::new();
-    let mut is_file = true;
-    let mut curr_id = 0usize;
-    for c in input.trim().chars() {
-        if is_file {
-            blocks.extend(repeat_n(Some(curr_id), char_to_digit(c)));
-            curr_id += 1;
-        } else {
-            blocks.extend(repeat_n(None, char_to_digit(c)));
+/// Which free span a file (or block) is moved to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fit {
+    /// The leftmost span it fits in.
+    First,
+    /// The smallest span it fits in, the leftmost of those on ties.
+    Best,
+}
+
+/// What is moved at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Granularity {
+    /// Single blocks, so files may be split across spans.
+    Blocks,
+    /// Whole files, which are only moved if a span fits them.
+    WholeFile,
+}
+
+/// How to compact a disk. Files are moved once each, starting from the last one,
+/// and only to free spans left of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Strategy {
+    fit: Fit,
+    granularity: Granularity,
+}
+
+impl Strategy {
+    /// The compaction of part one.
+    const BLOCKS_FIRST_FIT: Strategy = Strategy {
+        fit: Fit::First,
+        granularity: Granularity::Blocks,
+    };
+
+    /// The compaction of part two.
+    const WHOLE_FILE_FIRST_FIT: Strategy = Strategy {
+        fit: Fit::First,
+        granularity: Granularity::WholeFile,
+    };
+
+    const ALL: [Strategy; 4] = [
+        Strategy::BLOCKS_FIRST_FIT,
+        Strategy {
+            fit: Fit::Best,
+            granularity: Granularity::Blocks,
+        },
+        Strategy::WHOLE_FILE_FIRST_FIT,
+        Strategy {
+            fit: Fit::Best,
+            granularity: Granularity::WholeFile,
+        },
+    ];
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let fit = match self.fit {
+            Fit::First => "first-fit",
+            Fit::Best => "best-fit",
+        };
+        let granularity = match self.granularity {
+            Granularity::Blocks => "blocks",
+            Granularity::WholeFile => "whole files",
+        };
+        write!(f, "{granularity}, {fit}")
+    }
+}
+
+/// Contiguous blocks, either of a file or free.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: Index,
+    len: usize,
+}
+
+/// Part of a file stored in contiguous blocks. Files split by compaction have several extents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Extent {
+    id: FileId,
+    span: Span,
+}
+
+/// Free spans indexed by their length, so that a span fitting a file is found without scanning the disk.
+/// Each length has a min-heap of span starts.
+struct FreeSpans {
+    by_len: Vec<BinaryHeap<Reverse<Index>>>,
+}
+
+impl FreeSpans {
+    fn new(spans: &[Span]) -> Self {
+        let max_len = spans.iter().map(|span| span.len).max().unwrap_or(0);
+        let mut by_len = vec![BinaryHeap::new(); max_len + 1];
+        for span in spans.iter().filter(|span| span.len > 0) {
+            by_len[span.len].push(Reverse(span.start));
         }
-        is_file = !is_file;
-    }
-
-    let mut free_idx = 0usize;
-    loop {
-        //print_blocks(&blocks);
-        match blocks.get(free_idx) {
-            Some(Some(_)) => (), // do nothing
-            None => break,       // we've reached the end of the blocks
-            Some(None) => loop {
-                if let Some(Some(_)) = blocks.last() {
-                    blocks.swap_remove(free_idx);
-                    break;
-                }
-                let _ = blocks.pop();
-            },
+        FreeSpans { by_len }
+    }
+
+    /// Removes and returns the span chosen by `fit` among those of at least `min_len` blocks that start before `before`.
+    fn take(&mut self, min_len: usize, before: Index, fit: Fit) -> Option<Span> {
+        let candidates = (min_len.max(1)..self.by_len.len()).filter_map(|len| {
+            let Reverse(start) = *self.by_len[len].peek()?;
+            (start < before).then_some(Span { start, len })
+        });
+
+        let span = match fit {
+            Fit::First => candidates.min_by_key(|span| span.start),
+            Fit::Best => candidates.min_by_key(|span| span.len),
+        }?;
+        self.by_len[span.len].pop();
+        Some(span)
+    }
+
+    fn insert(&mut self, span: Span) {
+        if span.len > 0 {
+            self.by_len[span.len].push(Reverse(span.start));
+        }
+    }
+}
+
+/// Fragmentation of the free space and files of a disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fragmentation {
+    free_blocks: usize,
+    free_spans: usize,
+    largest_free_span: usize,
+    /// Files stored in more than one extent.
+    split_files: usize,
+}
+
+impl Fragmentation {
+    /// Share of the free space outside the largest free span, from 0 (one span) to almost 1 (single blocks).
+    fn external(&self) -> f64 {
+        if self.free_blocks == 0 {
+            return 0.0;
         }
-        free_idx += 1;
+        1.0 - self.largest_free_span as f64 / self.free_blocks as f64
     }
+}
 
-    Some(
-        blocks
-            .iter()
-            .enumerate()
-            .filter_map(|(i, b)| b.as_ref().map(|int| (i * *int) as u64))
-            .sum::<u64>(),
-    )
+impl Display for Fragmentation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} free blocks in {} spans (largest {}, {:.1}% fragmented), {} split files",
+            self.free_blocks,
+            self.free_spans,
+            self.largest_free_span,
+            self.external() * 100.0,
+            self.split_files
+        )
+    }
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let mut blocks: Vec<Option<FileId>> = Vec::new();
-    let mut file_start_idxs: Vec<(FileSize, Index)> = Vec::new();
-    let mut is_file = true;
-    let mut curr_id = 0usize;
-    for c in input.trim().chars() {
-        let block_count: FileSize = char_to_digit(c);
-        if is_file {
-            file_start_idxs.push((block_count, blocks.len()));
-            blocks.extend(repeat_n(Some(curr_id), block_count));
-            curr_id += 1;
-        } else {
-            blocks.extend(repeat_n(None, block_count));
+/// The blocks of a disk, as files and the free space between them.
+#[derive(Debug, Clone)]
+struct DiskMap {
+    /// Sorted by file id, and by position within a file.
+    extents: Vec<Extent>,
+    len: usize,
+}
+
+impl DiskMap {
+    fn parse(input: &str) -> Self {
+        let mut extents = vec![];
+        let mut position = 0;
+
+        for (i, c) in input.trim().chars().enumerate() {
+            let len = char_to_digit(c);
+            if i % 2 == 0 && len > 0 {
+                extents.push(Extent {
+                    id: i / 2,
+                    span: Span {
+                        start: position,
+                        len,
+                    },
+                });
+            }
+            position += len;
+        }
+
+        DiskMap {
+            extents,
+            len: position,
+        }
+    }
+
+    /// Free spans, in order. Adjacent free blocks are a single span, even around files of size 0.
+    fn free_spans(&self) -> Vec<Span> {
+        let mut used: Vec<Span> = self.extents.iter().map(|extent| extent.span).collect();
+        used.sort_unstable_by_key(|span| span.start);
+
+        let mut free = vec![];
+        let mut position = 0;
+        for span in used.iter().chain([&Span {
+            start: self.len,
+            len: 0,
+        }]) {
+            if span.start > position {
+                free.push(Span {
+                    start: position,
+                    len: span.start - position,
+                });
+            }
+            position = span.start + span.len;
         }
-        is_file = !is_file;
-    }
-
-    let mut inner_iter: Index;
-    let mut inner_lookahead: Index;
-    let mut swapped: bool;
-    // iterate through each placed file in reverse order
-    while let Some((size, idx)) = file_start_idxs.pop() {
-        //print_blocks(&blocks);
-        inner_iter = 0;
-        swapped = false;
-        // starting from the front of the file,
-        loop {
-            // check the contents of the next block
-            match blocks.get(inner_iter) {
-                Some(Some(_)) => (), // do nothing if there's a file there
-                None => break,       // we've reached the end of the blocks, go to next file
-                Some(None) => {
-                    // free space!
-                    inner_lookahead = inner_iter;
-                    // starting from this free space, move forward and check if there are enough
-                    // free spaces to fit the current file
-                    loop {
-                        match blocks.get(inner_lookahead) {
-                            Some(None) => {
-                                // still free space
-                                // swap if we have looked far enough ahead to fit the file
-                                if (inner_lookahead - inner_iter) == (size - 1) {
-                                    (inner_iter..=inner_lookahead)
-                                        .zip(idx..(idx + size))
-                                        .for_each(|(free_idx, file_idx)| {
-                                            blocks.swap(free_idx, file_idx)
-                                        });
-                                    swapped = true;
-                                    break;
-                                }
-                            }
-                            None => break, // end of blocks, leave
-                            Some(Some(_)) => {
-                                // hit another file before finding enough space to
-                                // fit current one, advance iterator to this point
-                                // to avoid rechecking what we already checked
-                                inner_iter = inner_lookahead;
-                                break;
-                            }
+        free
+    }
+
+    fn compact(&mut self, strategy: Strategy) {
+        let mut free = FreeSpans::new(&self.free_spans());
+        let mut extents = Vec::with_capacity(self.extents.len());
+
+        // before compaction, each file is a single extent.
+        for extent in self.extents.drain(..).rev() {
+            let Extent { id, span: file } = extent;
+
+            match strategy.granularity {
+                Granularity::WholeFile => {
+                    let moved = free.take(file.len, file.start, strategy.fit).map(|span| {
+                        free.insert(Span {
+                            start: span.start + file.len,
+                            len: span.len - file.len,
+                        });
+                        Span {
+                            start: span.start,
+                            len: file.len,
                         }
-                        inner_lookahead += 1;
+                    });
+                    extents.push(Extent {
+                        id,
+                        span: moved.unwrap_or(file),
+                    });
+                }
+                Granularity::Blocks => {
+                    // blocks are moved from the end of the file, so the unmoved ones stay at its start.
+                    let mut remaining = file.len;
+                    while remaining > 0 {
+                        let Some(span) = free.take(1, file.start, strategy.fit) else {
+                            break;
+                        };
+                        let moved = span.len.min(remaining);
+                        free.insert(Span {
+                            start: span.start + moved,
+                            len: span.len - moved,
+                        });
+                        extents.push(Extent {
+                            id,
+                            span: Span {
+                                start: span.start,
+                                len: moved,
+                            },
+                        });
+                        remaining -= moved;
                     }
-                    // if a swap occurred, break out of secondary loop
-                    if swapped {
-                        break;
+                    if remaining > 0 {
+                        extents.push(Extent {
+                            id,
+                            span: Span {
+                                start: file.start,
+                                len: remaining,
+                            },
+                        });
                     }
                 }
             }
-            inner_iter += 1;
-            // if the iterator is past the start point of the current file, there's no space for it
-            if inner_iter >= idx {
-                break;
-            }
         }
+
+        extents.sort_by_key(|extent| (extent.id, extent.span.start));
+        self.extents = extents;
     }
 
-    Some(
-        blocks
+    fn compacted(mut self, strategy: Strategy) -> Self {
+        self.compact(strategy);
+        self
+    }
+
+    fn checksum(&self) -> u64 {
+        self.extents
             .iter()
-            .enumerate()
-            .filter_map(|(i, b)| b.as_ref().map(|int| (i * *int) as u64))
-            .sum::<u64>(),
+            .map(|Extent { id, span }| {
+                // sum of `position * id` over the blocks of the extent.
+                let positions = span.len * (2 * span.start + span.len.saturating_sub(1)) / 2;
+                (id * positions) as u64
+            })
+            .sum()
+    }
+
+    fn fragmentation(&self) -> Fragmentation {
+        let free = self.free_spans();
+        let split_files = self
+            .extents
+            .chunk_by(|a, b| a.id == b.id)
+            .filter(|file| file.len() > 1)
+            .count();
+
+        Fragmentation {
+            free_blocks: free.iter().map(|span| span.len).sum(),
+            free_spans: free.len(),
+            largest_free_span: free.iter().map(|span| span.len).max().unwrap_or(0),
+            split_files,
+        }
+    }
+
+    /// The block layout, with file ids in base 36 (so ids above 35 repeat) and free blocks as `.`.
+    fn layout(&self) -> String {
+        let mut blocks = vec!['.'; self.len];
+        for Extent { id, span } in &self.extents {
+            let c = char::from_digit((id % 36) as u32, 36).unwrap();
+            blocks[span.start..span.start + span.len].fill(c);
+        }
+        blocks.into_iter().collect()
+    }
+}
+
+/// Disks up to this many blocks have their layout printed by `--explain`.
+const MAX_LAYOUT_LEN: usize = 200;
+
+/// Compares the fragmentation after compacting with each strategy.
+pub fn explain(input: &str) -> String {
+    let disk = DiskMap::parse(input);
+    let mut output = String::new();
+
+    let mut describe = |name: &str, disk: &DiskMap| {
+        writeln!(output, "{name}: {}", disk.fragmentation()).unwrap();
+        if disk.len <= MAX_LAYOUT_LEN {
+            writeln!(output, "  {}", disk.layout()).unwrap();
+        }
+    };
+
+    describe("uncompacted", &disk);
+    for strategy in Strategy::ALL {
+        let compacted = disk.clone().compacted(strategy);
+        describe(
+            &format!("{strategy} (checksum {})", compacted.checksum()),
+            &compacted,
+        );
+    }
+
+    output
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    Some(
+        DiskMap::parse(input)
+            .compacted(Strategy::BLOCKS_FIRST_FIT)
+            .checksum(),
+    )
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    Some(
+        DiskMap::parse(input)
+            .compacted(Strategy::WHOLE_FILE_FIRST_FIT)
+            .checksum(),
     )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::gen;
+    use advent_of_code::template::differential::{shrink, Differential};
 
     #[test]
     fn test_part_one() {
@@ -165,4 +388,134 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(2858));
     }
+
+    #[test]
+    fn renders_layouts() {
+        let disk = DiskMap::parse(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(disk.layout(), "00...111...2...333.44.5555.6666.777.888899");
+        assert_eq!(
+            disk.clone().compacted(Strategy::BLOCKS_FIRST_FIT).layout(),
+            "0099811188827773336446555566.............."
+        );
+        assert_eq!(
+            disk.compacted(Strategy::WHOLE_FILE_FIRST_FIT).layout(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+    }
+
+    #[test]
+    fn reports_fragmentation() {
+        let disk = DiskMap::parse("12345");
+        assert_eq!(
+            disk.fragmentation(),
+            Fragmentation {
+                free_blocks: 6,
+                free_spans: 2,
+                largest_free_span: 4,
+                split_files: 0
+            }
+        );
+
+        let compacted = disk.compacted(Strategy::BLOCKS_FIRST_FIT);
+        assert_eq!(compacted.layout(), "022111222......");
+        assert_eq!(compacted.fragmentation().free_spans, 1);
+        assert_eq!(compacted.fragmentation().split_files, 1);
+    }
+
+    #[test]
+    fn handles_empty_files() {
+        // the empty files 1 and 2 do not split the free space around them.
+        let disk = DiskMap::parse("1203021");
+        assert_eq!(disk.free_spans(), [Span { start: 1, len: 7 }]);
+        assert_eq!(
+            disk.compacted(Strategy::WHOLE_FILE_FIRST_FIT).layout(),
+            "03......."
+        );
+    }
+
+    /// Compacts a block per block copy of the disk, scanning it for free space.
+    fn compact_blocks(input: &str, strategy: Strategy) -> u64 {
+        let mut blocks: Vec<Option<FileId>> = vec![];
+        let mut files = vec![];
+        for (i, c) in input.trim().chars().enumerate() {
+            let len = char_to_digit(c);
+            if i % 2 == 0 {
+                files.push((i / 2, blocks.len(), len));
+            }
+            blocks.extend(std::iter::repeat_n((i % 2 == 0).then_some(i / 2), len));
+        }
+
+        // maximal runs of free blocks before `end`.
+        let free_runs = |blocks: &[Option<FileId>], end: Index| {
+            blocks[..end]
+                .chunk_by(|a, b| a.is_none() == b.is_none())
+                .scan(0, |start, run| {
+                    let span = Span {
+                        start: *start,
+                        len: run.len(),
+                    };
+                    *start += run.len();
+                    Some((span, run[0].is_none()))
+                })
+                .filter(|(_, free)| *free)
+                .map(|(span, _)| span)
+                .collect::<Vec<_>>()
+        };
+
+        for (id, start, len) in files.into_iter().rev() {
+            let pick = |runs: Vec<Span>, min_len: usize| {
+                let runs = runs.into_iter().filter(|run| run.len >= min_len);
+                match strategy.fit {
+                    Fit::First => runs.min_by_key(|run| run.start),
+                    Fit::Best => runs.min_by_key(|run| (run.len, run.start)),
+                }
+            };
+
+            match strategy.granularity {
+                Granularity::WholeFile => {
+                    if let Some(run) = pick(free_runs(&blocks, start), len) {
+                        blocks[start..start + len].fill(None);
+                        blocks[run.start..run.start + len].fill(Some(id));
+                    }
+                }
+                Granularity::Blocks => {
+                    for block in (start..start + len).rev() {
+                        let Some(run) = pick(free_runs(&blocks, start), 1) else {
+                            break;
+                        };
+                        blocks[block] = None;
+                        blocks[run.start] = Some(id);
+                    }
+                }
+            }
+        }
+
+        blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, block)| block.map(|id| (i * id) as u64))
+            .sum()
+    }
+
+    #[test]
+    fn compaction_matches_block_copy() {
+        for strategy in Strategy::ALL {
+            Differential::new(
+                |rng| {
+                    let size = rng.below(40);
+                    gen::day09(rng, size).trim().to_string()
+                },
+                |input| compact_blocks(input, strategy),
+                |input| DiskMap::parse(input).compacted(strategy).checksum(),
+            )
+            .shrink(|input| {
+                shrink::vec(input.as_bytes())
+                    .into_iter()
+                    .map(|digits| String::from_utf8(digits).unwrap())
+                    .collect()
+            })
+            .format(|input| format!("{input} ({strategy})"))
+            .assert();
+        }
+    }
 }
@@ -1,8 +1,9 @@
+use advent_of_code::template::bitmap::Bitmap;
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::{Match, Regex};
 
-advent_of_code::solution!(14);
+advent_of_code::solution!(14, render = render);
 
 type Int = i32;
 
@@ -60,24 +61,11 @@ impl Robot {
         }
     }
 
-    fn step(&mut self, x_bound: Int, y_bound: Int) {
-        self.pos = self.pos + self.vel;
-        if self.pos.x < 0 {
-            self.pos.x += x_bound;
-        } else if self.pos.x >= x_bound {
-            self.pos.x -= x_bound;
-        }
-
-        if self.pos.y < 0 {
-            self.pos.y += y_bound;
-        } else if self.pos.y >= y_bound {
-            self.pos.y -= y_bound;
-        }
-    }
-
-    fn step_n(&mut self, n: usize, x_bound: Int, y_bound: Int) {
-        for _ in 0..n {
-            self.step(x_bound, y_bound);
+    /// The position after `seconds`, wrapping around the `width` by `height` space.
+    fn at(&self, seconds: Int, width: Int, height: Int) -> Pos {
+        Pos {
+            x: (self.pos.x + self.vel.x * seconds).rem_euclid(width),
+            y: (self.pos.y + self.vel.y * seconds).rem_euclid(height),
         }
     }
 }
@@ -93,9 +81,8 @@ pub fn part_one(input: &str) -> Option<u32> {
     let bot_positions = input
         .lines()
         .map(|l| {
-            let mut robot = Robot::from_str(l);
-            robot.step_n(100, w, h);
-            robot.pos
+            let robot = Robot::from_str(l);
+            robot.at(100, w, h)
         })
         .collect_vec();
     let quad_cnts = bot_positions
@@ -110,37 +97,98 @@ pub fn part_one(input: &str) -> Option<u32> {
     Some(quad_cnts.0 * quad_cnts.1 * quad_cnts.2 * quad_cnts.3)
 }
 
+/// Width and height of the space of the real puzzle, both prime.
+const WIDTH: Int = 101;
+const HEIGHT: Int = 103;
+
+/// How spread out the coordinates are, as `n²` times their variance.
+fn spread(coords: impl Iterator<Item = Int>) -> i64 {
+    let (n, sum, sum_sq) = coords.fold((0i64, 0i64, 0i64), |(n, sum, sum_sq), c| {
+        (
+            n + 1,
+            sum + i64::from(c),
+            sum_sq + i64::from(c) * i64::from(c),
+        )
+    });
+    n * sum_sq - sum * sum
+}
+
+/// The second in `0..period` at which `coord` is the least spread out.
+fn tightest(robots: &[Robot], period: Int, coord: impl Fn(&Robot, Int) -> Int) -> Int {
+    (0..period)
+        .min_by_key(|t| spread(robots.iter().map(|robot| coord(robot, *t))))
+        .unwrap_or(0)
+}
+
+/// The inverse of `a` modulo `m`, if they are coprime.
+fn mod_inverse(a: Int, m: Int) -> Option<Int> {
+    let (mut r0, mut r1) = (a.rem_euclid(m), m);
+    let (mut s0, mut s1) = (1, 0);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    (r0 == 1).then(|| s0.rem_euclid(m))
+}
+
+/// The first second at which the robots draw the tree.
+///
+/// Robots drawing the picture bunch up in the same columns every `WIDTH` seconds and in the same
+/// rows every `HEIGHT` seconds, as x repeats with period `WIDTH` and y with period `HEIGHT`. Both
+/// are found as the second of least variance of that axis, and combined with the Chinese
+/// remainder theorem.
+fn easter_egg(robots: &[Robot]) -> Int {
+    let tx = tightest(robots, WIDTH, |robot, t| {
+        (robot.pos.x + robot.vel.x * t).rem_euclid(WIDTH)
+    });
+    let ty = tightest(robots, HEIGHT, |robot, t| {
+        (robot.pos.y + robot.vel.y * t).rem_euclid(HEIGHT)
+    });
+    // t = tx + WIDTH * k with t = ty (mod HEIGHT).
+    let inverse = mod_inverse(WIDTH, HEIGHT).expect("the width and height are coprime");
+    tx + WIDTH * ((ty - tx) * inverse).rem_euclid(HEIGHT)
+}
+
 pub fn part_two(input: &str) -> Option<u32> {
-    let w = 101;
-    let h = 103;
-    let mut bots: Vec<Robot> = input.lines().map(Robot::from_str).collect_vec();
-    let mut min_sec_val = (-1 as Int, Int::MAX);
-    for i in 0..10000 {
-        let quad_cnts = bots
-            .iter()
-            .fold((0, 0, 0, 0), |(q1, q2, q3, q4), bot| match bot.pos {
-                Pos { x, y } if x < (w / 2) && y < (h / 2) => (q1 + 1, q2, q3, q4),
-                Pos { x, y } if x > (w / 2) && y < (h / 2) => (q1, q2 + 1, q3, q4),
-                Pos { x, y } if x < (w / 2) && y > (h / 2) => (q1, q2, q3 + 1, q4),
-                Pos { x, y } if x > (w / 2) && y > (h / 2) => (q1, q2, q3, q4 + 1),
-                _ => (q1, q2, q3, q4),
-            });
-        //let mut grid: [[usize; 101]; 103] = [[0; 101]; 103];
-        //bots.iter().for_each(|bot| grid[bot.pos.y as usize][bot.pos.x as usize] += 1);
-        //println!("step: {}\n{}\n", i, grid.iter().map(|row| row.iter().map(|cell| if *cell == 0 { ".".to_string() } else { "#".to_string() }).join(" ")).join("\n"));
-        let sec_val = quad_cnts.0 * quad_cnts.1 * quad_cnts.2 * quad_cnts.3;
-        if sec_val < min_sec_val.1 {
-            min_sec_val = (i as Int, sec_val);
-        }
-        bots.iter_mut().for_each(|bot| bot.step(w, h));
+    let robots = input.lines().map(Robot::from_str).collect_vec();
+    Some(easter_egg(&robots) as u32)
+}
+
+/// Part two by looking at every frame: the second with the least variance of both axes.
+#[cfg(test)]
+fn brute_part_two(input: &str) -> Option<u32> {
+    let robots = input.lines().map(Robot::from_str).collect_vec();
+    (0..WIDTH * HEIGHT)
+        .min_by_key(|t| {
+            let positions = robots
+                .iter()
+                .map(|robot| robot.at(*t, WIDTH, HEIGHT))
+                .collect_vec();
+            spread(positions.iter().map(|pos| pos.x)) + spread(positions.iter().map(|pos| pos.y))
+        })
+        .map(|t| t as u32)
+}
+
+/// The robots at the second of part two.
+fn render(input: &str) -> Bitmap {
+    let robots = input.lines().map(Robot::from_str).collect_vec();
+    let second = easter_egg(&robots);
+    let mut bitmap = Bitmap::new(WIDTH as usize, HEIGHT as usize);
+    for robot in &robots {
+        let pos = robot.at(second, WIDTH, HEIGHT);
+        bitmap.set(pos.x as usize, pos.y as usize, true);
     }
-    println!("{}", min_sec_val.0);
-    None
+    bitmap
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::{
+        gen,
+        template::{differential::Differential, rng::Rng},
+    };
 
     #[test]
     fn test_part_one() {
@@ -150,7 +198,46 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+        // the example has no tree, as it is meant for a smaller space.
+        let input = gen::day14_tree(&mut Rng::new(14), 500, 6_512);
+        assert_eq!(part_two(&input), Some(6_512));
+    }
+
+    #[test]
+    fn renders_the_tree() {
+        let input = gen::day14_tree(&mut Rng::new(14), 500, 6_512);
+        let tree = render(&input).to_ascii();
+        // the 30 rows of the tree hold most robots, against about 150 for any 30 rows at other seconds.
+        let per_row = tree
+            .lines()
+            .map(|row| row.matches('#').count())
+            .collect_vec();
+        let densest = per_row
+            .windows(30)
+            .map(|rows| rows.iter().sum::<usize>())
+            .max();
+        assert!(densest > Some(200), "{tree}");
+    }
+
+    #[test]
+    fn inverts_modulo() {
+        assert_eq!(mod_inverse(WIDTH, HEIGHT), Some(51));
+        assert_eq!((WIDTH * 51) % HEIGHT, 1);
+        assert_eq!(mod_inverse(4, 6), None);
+    }
+
+    #[test]
+    fn matches_every_frame() {
+        Differential::new(
+            |rng| {
+                let size = rng.below(300) + 100;
+                gen::day14(rng, size)
+            },
+            |input: &String| brute_part_two(input),
+            |input: &String| part_two(input),
+        )
+        .format(Clone::clone)
+        .cases(20)
+        .assert();
     }
 }
@@ -1,8 +1,26 @@
-use std::fmt::Display;
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    fs,
+    io::{self, stdout, Write},
+    iter,
+    ops::RangeInclusive,
+    path::PathBuf,
+    process,
+    time::Duration,
+};
 
+use advent_of_code::clap::{self, Parser};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use fxhash::FxHashSet;
 use itertools::Itertools;
 
-advent_of_code::solution!(15);
+advent_of_code::solution!(15, interactive = simulate);
 
 type Int = i32;
 
@@ -43,242 +61,617 @@ impl std::ops::Sub for Pos {
     }
 }
 
-#[derive(Clone, Debug)]
-enum Tile {
+type CrateId = usize;
+
+/// What occupies a cell of the warehouse. The robot is tracked separately.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Cell {
     Empty,
     Wall,
-    BigBoxLeft,
-    BigBoxRight,
-    Box,
-    Robot,
+    Crate(CrateId),
+}
+
+/// A box of any shape, moved as a whole.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Crate {
+    /// The top left corner of the footprint, which gives the GPS coordinate.
+    pos: Pos,
+    /// The cells covered by the crate relative to `pos`, with no negative coordinates.
+    footprint: Vec<Delta>,
+}
+
+impl Crate {
+    fn cells(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.footprint.iter().map(|d| self.pos + *d)
+    }
+}
+
+/// The robot position before a move and the crates it pushed, so that the move can be undone.
+#[derive(Clone, Debug)]
+struct Change {
+    bot_pos: Pos,
+    delta: Delta,
+    pushed: Vec<CrateId>,
 }
 
 #[derive(Clone, Debug)]
 struct WarehouseMap {
-    map: Vec<Vec<Tile>>,
+    width: Int,
+    height: Int,
+    cells: Vec<Cell>,
+    crates: Vec<Crate>,
     bot_pos: Pos,
 }
 
 impl Display for WarehouseMap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.map
-                .iter()
-                .map(|line| line
-                    .iter()
-                    .map(|t| match t {
-                        Tile::Empty => '.',
-                        Tile::Wall => '#',
-                        Tile::Box => 'O',
-                        Tile::BigBoxRight => ']',
-                        Tile::BigBoxLeft => '[',
-                        Tile::Robot => '@',
-                    })
-                    .join(" "))
-                .join("\n")
-        )
+        let single = [Pos { x: 0, y: 0 }];
+        let double = [Pos { x: 0, y: 0 }, Pos { x: 1, y: 0 }];
+        for y in 0..self.height {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for x in 0..self.width {
+                let pos = Pos { x, y };
+                let c = match self.get(pos) {
+                    _ if pos == self.bot_pos => '@',
+                    Cell::Empty => '.',
+                    Cell::Wall => '#',
+                    Cell::Crate(id) => {
+                        let cr = &self.crates[id];
+                        if cr.footprint == single {
+                            'O'
+                        } else if cr.footprint == double {
+                            if pos == cr.pos {
+                                '['
+                            } else {
+                                ']'
+                            }
+                        } else {
+                            // other shapes are told apart by letter, as in `parse`.
+                            (b'a' + (id % 26) as u8) as char
+                        }
+                    }
+                };
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
     }
 }
 
 impl WarehouseMap {
-    fn from_doubled_str(input: &str) -> Self {
-        let mut bot_pos: Pos = Pos { x: 0, y: 0 };
-        let map: Vec<Vec<Tile>> = input
-            .lines()
-            .enumerate()
-            .filter(|(_, line)| !line.is_empty())
-            .map(|(y, line)| {
-                line.chars()
-                    .enumerate()
-                    .flat_map(|(x, c)| match c {
-                        '.' => vec![Tile::Empty, Tile::Empty],
-                        '#' => vec![Tile::Wall, Tile::Wall],
-                        'O' => vec![Tile::BigBoxLeft, Tile::BigBoxRight],
-                        '@' => {
-                            bot_pos.x = TryInto::<Int>::try_into(x).unwrap() * 2;
-                            bot_pos.y = y.try_into().unwrap();
-                            vec![Tile::Robot, Tile::Empty]
-                        }
-                        _ => panic!("wtf is this: {}", c),
-                    })
-                    .collect::<Vec<Tile>>()
-            })
-            .collect();
-        let row_len = map.first().unwrap().len() as i32;
-        assert!(map.iter().skip(1).all(|row| (row.len() as i32) == row_len));
-        Self { map, bot_pos }
+    fn new(width: Int, height: Int) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::Empty; (width * height) as usize],
+            crates: vec![],
+            bot_pos: Pos { x: 0, y: 0 },
+        }
     }
 
-    fn from_str(input: &str) -> Self {
-        let mut bot_pos: Pos = Pos { x: 0, y: 0 };
-        let map: Vec<Vec<Tile>> = input
-            .lines()
-            .enumerate()
-            .filter(|(_, line)| !line.is_empty())
-            .map(|(y, line)| {
-                line.chars()
-                    .enumerate()
-                    .map(|(x, c)| match c {
-                        '.' => Tile::Empty,
-                        '#' => Tile::Wall,
-                        'O' => Tile::Box,
-                        '@' => {
-                            bot_pos.x = x.try_into().unwrap();
-                            bot_pos.y = y.try_into().unwrap();
-                            Tile::Robot
-                        }
-                        _ => panic!("wtf is this: {}", c),
-                    })
-                    .collect()
-            })
-            .collect();
-        let row_len = map.first().unwrap().len() as i32;
-        assert!(map.iter().skip(1).all(|row| (row.len() as i32) == row_len));
-        Self { map, bot_pos }
-    }
-
-    fn get(&self, pos: Pos) -> Option<&Tile> {
-        self.map.get(pos.y as usize)?.get(pos.x as usize)
-    }
-
-    fn shift(&mut self, pos: &Pos, delta: &Delta) {
-        let new_pos: Pos = *pos + *delta;
-        *self
-            .map
-            .get_mut(new_pos.y as usize)
-            .expect("bad y")
-            .get_mut(new_pos.x as usize)
-            .expect("bad x") = self.get(*pos).expect("bad pos").clone();
-        *self
-            .map
-            .get_mut(pos.y as usize)
-            .expect("bad y")
-            .get_mut(pos.x as usize)
-            .expect("bad x") = Tile::Empty;
-    }
-
-    fn push(&mut self, delta: Delta) {
-        let mut to_move: Vec<Pos> = vec![self.bot_pos];
-        // (<position in lane to check>, <whether it is known to be clear/blocked>)
-        let mut maybe_blocked_lanes: Vec<(Pos, Option<bool>)> = vec![(self.bot_pos, None)];
-        let mut new_maybe_blocked: Vec<Pos> = vec![];
-        loop {
-            for (curr_pos, is_clear) in maybe_blocked_lanes
-                .iter_mut()
-                .filter(|(_, is_clear)| is_clear.is_none())
-            {
-                *curr_pos += delta;
-                match self.get(*curr_pos) {
-                    Some(Tile::BigBoxLeft) if delta.y != 0 => {
-                        if !to_move.contains(curr_pos) {
-                            to_move.push(*curr_pos);
-                        }
-                        let other_half_pos = *curr_pos + Pos { x: 1, y: 0 };
-                        if !to_move.contains(&other_half_pos) {
-                            to_move.push(other_half_pos);
-                            new_maybe_blocked.push(other_half_pos);
-                        }
+    /// Parses a map where `O` is a single crate, `[]` a double-wide one, and connected cells
+    /// with the same letter make up a crate of any shape.
+    fn parse(input: &str) -> Self {
+        let rows = input.lines().filter(|line| !line.is_empty()).collect_vec();
+        let width = rows.first().map_or(0, |row| row.len()) as Int;
+        assert!(rows.iter().all(|row| row.len() as Int == width));
+        let mut warehouse = Self::new(width, rows.len() as Int);
+        let char_at = |pos: Pos| {
+            rows.get(pos.y as usize)
+                .and_then(|row| row.as_bytes().get(pos.x as usize))
+                .map(|b| *b as char)
+        };
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let pos = Pos {
+                    x: x as Int,
+                    y: y as Int,
+                };
+                match c {
+                    '.' => {}
+                    '#' => warehouse.set(pos, Cell::Wall),
+                    '@' => warehouse.bot_pos = pos,
+                    'O' => warehouse.add_crate(vec![pos]),
+                    '[' if char_at(pos + Pos { x: 1, y: 0 }) == Some(']') => {
+                        warehouse.add_crate(vec![pos, pos + Pos { x: 1, y: 0 }]);
                     }
-                    Some(Tile::BigBoxRight) if delta.y != 0 => {
-                        if !to_move.contains(curr_pos) {
-                            to_move.push(*curr_pos);
+                    ']' if char_at(pos - Pos { x: 1, y: 0 }) == Some('[') => {}
+                    c if c.is_ascii_alphabetic() => {
+                        if warehouse.get(pos) != Cell::Empty {
+                            continue;
                         }
-                        let other_half_pos = *curr_pos + Pos { x: -1, y: 0 };
-                        if !to_move.contains(&other_half_pos) {
-                            to_move.push(*curr_pos + Pos { x: -1, y: 0 });
-                            new_maybe_blocked.push(other_half_pos);
+                        // flood fill the cells with the same letter.
+                        let mut cells = vec![pos];
+                        let mut seen: FxHashSet<Pos> = FxHashSet::from_iter([pos]);
+                        let mut i = 0;
+                        while let Some(&cell) = cells.get(i) {
+                            for delta in DIRECTIONS {
+                                let next = cell + delta;
+                                if char_at(next) == Some(c) && seen.insert(next) {
+                                    cells.push(next);
+                                }
+                            }
+                            i += 1;
                         }
+                        warehouse.add_crate(cells);
                     }
-                    Some(Tile::Box) | Some(Tile::BigBoxRight) | Some(Tile::BigBoxLeft) => {
-                        to_move.push(*curr_pos)
-                    }
-                    Some(Tile::Wall) => *is_clear = Some(false),
-                    Some(Tile::Empty) => *is_clear = Some(true),
-                    other => panic!("what do: {:?} @ {:?}", other, curr_pos),
+                    _ => panic!("wtf is this: {}", c),
                 }
             }
-            if !new_maybe_blocked.is_empty() {
-                new_maybe_blocked
-                    .iter()
-                    .for_each(|p| maybe_blocked_lanes.push((*p, None)));
-                new_maybe_blocked.clear();
-            } else if maybe_blocked_lanes
-                .iter()
-                .any(|(_, is_clear)| *is_clear == Some(false))
-            {
-                // do nothing
-                break;
-            } else if maybe_blocked_lanes
-                .iter()
-                .all(|(_, is_clear)| *is_clear == Some(true))
-            {
-                self.bot_pos += delta;
-                to_move
-                    .iter()
-                    .rev()
-                    .dedup()
-                    .for_each(|p| self.shift(p, &delta));
-                break;
+        }
+        warehouse
+    }
+
+    /// Parses a map in the puzzle's format with every tile made `width` cells wide and
+    /// `height` cells high, so that each crate covers `width` by `height` cells.
+    fn scaled(input: &str, width: Int, height: Int) -> Self {
+        let plain = Self::parse(input);
+        let mut warehouse = Self::new(plain.width * width, plain.height * height);
+        let scale = |pos: Pos| Pos {
+            x: pos.x * width,
+            y: pos.y * height,
+        };
+        let block = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Pos { x, y }))
+            .collect_vec();
+
+        warehouse.bot_pos = scale(plain.bot_pos);
+        for y in 0..plain.height {
+            for x in 0..plain.width {
+                let pos = Pos { x, y };
+                if plain.get(pos) == Cell::Wall {
+                    block
+                        .iter()
+                        .for_each(|d| warehouse.set(scale(pos) + *d, Cell::Wall));
+                }
             }
         }
+        for cr in &plain.crates {
+            let cells = cr
+                .cells()
+                .flat_map(|cell| block.iter().map(move |d| scale(cell) + *d))
+                .collect();
+            warehouse.add_crate(cells);
+        }
+        warehouse
     }
 
-    fn sum_gps(&self) -> u32 {
-        let mut sum = 0u32;
-        for y in 0..self.map.len() {
-            for x in 0..self.map.first().unwrap().len() {
-                if let Some(Tile::Box) | Some(Tile::BigBoxLeft) = self.get(Pos {
-                    x: x as Int,
-                    y: y as Int,
-                }) {
-                    sum += (x as u32) + (100 * (y as u32));
+    fn from_doubled_str(input: &str) -> Self {
+        Self::scaled(input, 2, 1)
+    }
+
+    fn index(&self, pos: Pos) -> Option<usize> {
+        ((0..self.width).contains(&pos.x) && (0..self.height).contains(&pos.y))
+            .then(|| (pos.y * self.width + pos.x) as usize)
+    }
+
+    /// The cell at `pos`, where everything outside the map is wall.
+    fn get(&self, pos: Pos) -> Cell {
+        self.index(pos).map_or(Cell::Wall, |i| self.cells[i])
+    }
+
+    fn set(&mut self, pos: Pos, cell: Cell) {
+        let i = self.index(pos).expect("bad pos");
+        self.cells[i] = cell;
+    }
+
+    fn add_crate(&mut self, cells: Vec<Pos>) {
+        let id = self.crates.len();
+        let pos = Pos {
+            x: cells.iter().map(|p| p.x).min().expect("empty crate"),
+            y: cells.iter().map(|p| p.y).min().expect("empty crate"),
+        };
+        for cell in &cells {
+            self.set(*cell, Cell::Crate(id));
+        }
+        self.crates.push(Crate {
+            pos,
+            footprint: cells.into_iter().map(|cell| cell - pos).collect(),
+        });
+    }
+
+    /// The crates that move if the robot moves by `delta`, or `None` if a wall is in the way.
+    fn pushed_crates(&self, delta: Delta) -> Option<Vec<CrateId>> {
+        let mut pushed: Vec<CrateId> = vec![];
+        let mut seen: FxHashSet<CrateId> = FxHashSet::default();
+        let mut frontier: VecDeque<Pos> = VecDeque::from([self.bot_pos + delta]);
+        while let Some(pos) = frontier.pop_front() {
+            match self.get(pos) {
+                Cell::Empty => {}
+                Cell::Wall => return None,
+                Cell::Crate(id) if !seen.insert(id) => {}
+                Cell::Crate(id) => {
+                    pushed.push(id);
+                    frontier.extend(self.crates[id].cells().map(|cell| cell + delta));
                 }
             }
         }
-        sum
+        Some(pushed)
+    }
+
+    fn shift(&mut self, pushed: &[CrateId], delta: Delta) {
+        for &id in pushed {
+            for cell in self.crates[id].cells().collect_vec() {
+                self.set(cell, Cell::Empty);
+            }
+        }
+        for &id in pushed {
+            self.crates[id].pos += delta;
+            for cell in self.crates[id].cells().collect_vec() {
+                self.set(cell, Cell::Crate(id));
+            }
+        }
+    }
+
+    /// Moves the robot by `delta`, pushing crates in the way. Returns `None` if something blocks the move.
+    fn push(&mut self, delta: Delta) -> Option<Change> {
+        let pushed = self.pushed_crates(delta)?;
+        self.shift(&pushed, delta);
+        let change = Change {
+            bot_pos: self.bot_pos,
+            delta,
+            pushed,
+        };
+        self.bot_pos += delta;
+        Some(change)
+    }
+
+    fn undo(&mut self, change: &Change) {
+        self.shift(&change.pushed, Pos { x: 0, y: 0 } - change.delta);
+        self.bot_pos = change.bot_pos;
+    }
+
+    fn sum_gps(&self) -> u32 {
+        self.crates
+            .iter()
+            .map(|cr| (cr.pos.x + 100 * cr.pos.y) as u32)
+            .sum()
     }
 }
 
+const DIRECTIONS: [Delta; 4] = [
+    Pos { x: 0, y: -1 },
+    Pos { x: 1, y: 0 },
+    Pos { x: 0, y: 1 },
+    Pos { x: -1, y: 0 },
+];
+
+/// The direction of a move in the puzzle's `^>v<` format.
+fn direction(c: char) -> Option<Delta> {
+    "^>v<".find(c).map(|i| DIRECTIONS[i])
+}
+
+fn run_moves(mut warehouse: WarehouseMap, moves: &str) -> u32 {
+    for c in moves.chars().filter(|c| *c != '\n') {
+        let delta = direction(c).unwrap_or_else(|| panic!("idk what to do with '{}'", c));
+        warehouse.push(delta);
+    }
+    warehouse.sum_gps()
+}
+
 pub fn part_one(input: &str) -> Option<u32> {
     let mut sections = input.split("\n\n");
-    let mut warehouse = WarehouseMap::from_str(sections.next()?);
-    for c in sections.next()?.chars() {
-        match c {
-            '^' => warehouse.push(Pos { x: 0, y: -1 }),
-            '>' => warehouse.push(Pos { x: 1, y: 0 }),
-            'v' => warehouse.push(Pos { x: 0, y: 1 }),
-            '<' => warehouse.push(Pos { x: -1, y: 0 }),
-            '\n' => (),
-            _ => panic!("idk what to do with '{}'", c),
+    let warehouse = WarehouseMap::parse(sections.next()?);
+    Some(run_moves(warehouse, sections.next()?))
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    let mut sections = input.split("\n\n");
+    let warehouse = WarehouseMap::from_doubled_str(sections.next()?);
+    Some(run_moves(warehouse, sections.next()?))
+}
+
+/// Part one on the grid of characters, shifting the row of boxes in front of the robot.
+#[cfg(test)]
+fn brute_part_one(input: &str) -> Option<u32> {
+    let (map, moves) = input.split_once("\n\n")?;
+    let mut grid = map
+        .lines()
+        .map(|line| line.as_bytes().to_vec())
+        .collect_vec();
+    let (mut x, mut y) = grid.iter().enumerate().find_map(|(y, row)| {
+        row.iter()
+            .position(|c| *c == b'@')
+            .map(|x| (x as Int, y as Int))
+    })?;
+
+    for delta in moves.chars().filter_map(direction) {
+        let mut end = (x + delta.x, y + delta.y);
+        while grid[end.1 as usize][end.0 as usize] == b'O' {
+            end = (end.0 + delta.x, end.1 + delta.y);
+        }
+        if grid[end.1 as usize][end.0 as usize] == b'.' {
+            grid[end.1 as usize][end.0 as usize] = b'O';
+            grid[y as usize][x as usize] = b'.';
+            (x, y) = (x + delta.x, y + delta.y);
+            grid[y as usize][x as usize] = b'@';
         }
-        //println!("push {}:\n{}\n", c, warehouse);
     }
-    Some(warehouse.sum_gps())
+
+    Some(
+        grid.iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, c)| **c == b'O')
+                    .map(move |(x, _)| x as u32 + 100 * y as u32)
+            })
+            .sum(),
+    )
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+/* -------------------------------------------------------------------------- */
+
+/// Moves per line of a recorded move log, like the puzzle's example.
+const LOG_LINE_LEN: usize = 70;
+
+/// A warehouse driven one move at a time, with undo and redo.
+struct Simulator {
+    warehouse: WarehouseMap,
+    /// Moves made so far, with the changes to undo them. Blocked moves have no change.
+    done: Vec<(char, Option<Change>)>,
+    /// Undone moves, the last one undone first.
+    undone: Vec<char>,
+}
+
+impl Simulator {
+    fn new(warehouse: WarehouseMap) -> Self {
+        Simulator {
+            warehouse,
+            done: vec![],
+            undone: vec![],
+        }
+    }
+
+    /// Makes a move, discarding the undone ones. Returns `false` for characters other than `^>v<`.
+    fn step(&mut self, c: char) -> bool {
+        self.undone.clear();
+        self.apply(c)
+    }
+
+    fn apply(&mut self, c: char) -> bool {
+        let Some(delta) = direction(c) else {
+            return false;
+        };
+        let change = self.warehouse.push(delta);
+        self.done.push((c, change));
+        true
+    }
+
+    fn undo(&mut self) -> bool {
+        let Some((c, change)) = self.done.pop() else {
+            return false;
+        };
+        if let Some(change) = change {
+            self.warehouse.undo(&change);
+        }
+        self.undone.push(c);
+        true
+    }
+
+    fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(c) => self.apply(c),
+            None => false,
+        }
+    }
+
+    /// The moves made so far in the puzzle's format, so they can be replayed.
+    fn log(&self) -> String {
+        self.done
+            .iter()
+            .map(|(c, _)| *c)
+            .chunks(LOG_LINE_LEN)
+            .into_iter()
+            .map(|line| line.collect::<String>() + "\n")
+            .collect()
+    }
+}
+
+/// Options of the interactive warehouse, passed after `--`.
+#[derive(Parser, Debug)]
+#[command(
+    name = "15 --interactive --",
+    about = "Move the robot with the arrow keys."
+)]
+struct SimulatorArgs {
+    /// Use the doubled map of part two.
+    #[arg(long)]
+    wide: bool,
+
+    /// Replay the moves in this file, or the moves of the input if no file is given.
+    /// The file may also be a whole puzzle input.
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "")]
+    replay: Option<PathBuf>,
+
+    /// Replayed moves per second, from 0.5 to 1000.
+    #[arg(long, default_value_t = 10.0, value_parser = parse_speed)]
+    speed: f64,
+
+    /// Write the moves made to this file when quitting.
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+}
+
+/// Replayed moves per second, as far as `+` and `-` go.
+const SPEEDS: RangeInclusive<f64> = 0.5..=1000.0;
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.parse().map_err(|e| format!("{e}"))?;
+    if speed > 0.0 && speed.is_finite() {
+        Ok(speed.clamp(*SPEEDS.start(), *SPEEDS.end()))
+    } else {
+        Err(format!("speed must be positive, got {s}"))
+    }
+}
+
+const HELP: &str =
+    "arrows: move  u: undo  r: redo  space: play/pause  n: next replayed move  +/-: speed  q: quit";
+
+/// Runs the warehouse in the terminal, moving the robot with the arrow keys.
+fn simulate(input: &str, args: &[String]) {
+    let args =
+        SimulatorArgs::try_parse_from(iter::once("15").chain(args.iter().map(String::as_str)))
+            .unwrap_or_else(|e| e.exit());
+
     let mut sections = input.split("\n\n");
-    let mut warehouse = WarehouseMap::from_doubled_str(sections.next()?);
-    for c in sections.next()?.chars() {
-        match c {
-            '^' => warehouse.push(Pos { x: 0, y: -1 }),
-            '>' => warehouse.push(Pos { x: 1, y: 0 }),
-            'v' => warehouse.push(Pos { x: 0, y: 1 }),
-            '<' => warehouse.push(Pos { x: -1, y: 0 }),
-            '\n' => (),
-            _ => panic!("idk what to do with '{}'", c),
+    let map = sections.next().unwrap_or_default();
+    let warehouse = if args.wide {
+        WarehouseMap::from_doubled_str(map)
+    } else {
+        WarehouseMap::parse(map)
+    };
+
+    let replay: VecDeque<char> = match &args.replay {
+        None => VecDeque::new(),
+        Some(path) if path.as_os_str().is_empty() => {
+            sections.next().unwrap_or_default().chars().collect()
+        }
+        Some(path) => {
+            let moves = fs::read_to_string(path).unwrap_or_else(|e| {
+                eprintln!("Failed to read \"{}\": {e}", path.display());
+                process::exit(1);
+            });
+            // a whole puzzle input has its moves after the map.
+            let moves = moves.rsplit("\n\n").next().unwrap_or_default();
+            moves.chars().collect()
+        }
+    };
+
+    let mut simulator = Simulator::new(warehouse);
+    if let Err(e) = run_terminal(&mut simulator, replay, args.speed) {
+        eprintln!("Failed to run the interactive warehouse: {e}");
+        process::exit(1);
+    }
+
+    if let Some(path) = &args.record {
+        match fs::write(path, simulator.log()) {
+            Ok(()) => println!(
+                "Recorded {} moves to \"{}\".",
+                simulator.done.len(),
+                path.display()
+            ),
+            Err(e) => eprintln!("Failed to record the moves: {e}"),
         }
-        //sleep_ms(300);
-        //println!("push {}:\n{}\n", c, warehouse);
     }
-    Some(warehouse.sum_gps())
+}
+
+/// Switches the terminal to raw mode and the alternate screen while alive.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn run_terminal(
+    simulator: &mut Simulator,
+    mut replay: VecDeque<char>,
+    mut speed: f64,
+) -> io::Result<()> {
+    let _terminal = RawTerminal::enter()?;
+    let replay_len = replay.len();
+    let mut playing = !replay.is_empty();
+
+    // replayed moves that are not directions, like line breaks, are skipped.
+    let next_replayed = |simulator: &mut Simulator, replay: &mut VecDeque<char>| {
+        while let Some(c) = replay.pop_front() {
+            if simulator.step(c) {
+                break;
+            }
+        }
+    };
+
+    loop {
+        let status = format!(
+            "GPS sum: {}   moves: {} ({} undone)   replay: {}/{} at {speed}/s{}",
+            simulator.warehouse.sum_gps(),
+            simulator.done.len(),
+            simulator.undone.len(),
+            replay_len - replay.len(),
+            replay_len,
+            if playing { "" } else { " (paused)" }
+        );
+        draw(&simulator.warehouse, &status)?;
+
+        let timeout = if playing && !replay.is_empty() {
+            Duration::from_secs_f64(1.0 / speed)
+        } else {
+            Duration::from_secs(3600)
+        };
+        if !event::poll(timeout)? {
+            if playing {
+                next_replayed(simulator, &mut replay);
+            }
+            continue;
+        }
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+
+        match code {
+            KeyCode::Up => _ = simulator.step('^'),
+            KeyCode::Right => _ = simulator.step('>'),
+            KeyCode::Down => _ = simulator.step('v'),
+            KeyCode::Left => _ = simulator.step('<'),
+            KeyCode::Char('u') => _ = simulator.undo(),
+            KeyCode::Char('r') => _ = simulator.redo(),
+            KeyCode::Char(' ') => playing = !playing,
+            KeyCode::Char('n') => next_replayed(simulator, &mut replay),
+            KeyCode::Char('+') => speed = (speed * 2.0).min(*SPEEDS.end()),
+            KeyCode::Char('-') => speed = (speed / 2.0).max(*SPEEDS.start()),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn draw(warehouse: &WarehouseMap, status: &str) -> io::Result<()> {
+    let mut out = stdout();
+    queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+    // raw mode does not return the cursor to the start of the line.
+    for line in warehouse.to_string().lines() {
+        write!(out, "{line}\r\n")?;
+    }
+    write!(out, "\r\n{status}\r\n{HELP}\r\n")?;
+    out.flush()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::{gen, template::differential::Differential};
+
+    fn after_moves(mut warehouse: WarehouseMap, moves: &str) -> String {
+        moves.chars().filter_map(direction).for_each(|delta| {
+            warehouse.push(delta);
+        });
+        warehouse.to_string()
+    }
 
     #[test]
     fn test_part_one() {
@@ -291,4 +684,203 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(9021));
     }
+
+    #[test]
+    fn undoes_and_redoes_moves() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let (map, moves) = input.split_once("\n\n").unwrap();
+
+        for warehouse in [
+            WarehouseMap::parse(map),
+            WarehouseMap::from_doubled_str(map),
+        ] {
+            let initial = warehouse.to_string();
+            let mut simulator = Simulator::new(warehouse);
+            for c in moves.chars() {
+                simulator.step(c);
+            }
+            let end = simulator.warehouse.to_string();
+            assert_eq!(simulator.done.len(), 700);
+
+            while simulator.undo() {}
+            assert_eq!(simulator.warehouse.to_string(), initial);
+
+            while simulator.redo() {}
+            assert_eq!(simulator.warehouse.to_string(), end);
+        }
+    }
+
+    #[test]
+    fn checks_the_speed() {
+        let speed =
+            |s: &str| SimulatorArgs::try_parse_from(["15", "--speed", s]).map(|args| args.speed);
+        assert_eq!(speed("20").ok(), Some(20.0));
+        assert_eq!(speed("5000").ok(), Some(1000.0));
+        assert_eq!(speed("0.1").ok(), Some(0.5));
+        assert!(speed("0").is_err());
+        assert!(speed("-3").is_err());
+        assert!(speed("NaN").is_err());
+    }
+
+    #[test]
+    fn records_replayable_moves() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let (map, moves) = input.split_once("\n\n").unwrap();
+
+        let mut simulator = Simulator::new(WarehouseMap::from_doubled_str(map));
+        moves.chars().for_each(|c| _ = simulator.step(c));
+        simulator.undo();
+        simulator.step('<');
+
+        let log = simulator.log();
+        assert!(log.lines().all(|line| line.len() <= LOG_LINE_LEN));
+        assert_eq!(
+            run_moves(WarehouseMap::from_doubled_str(map), &log),
+            simulator.warehouse.sum_gps()
+        );
+        assert_eq!(simulator.undone, []);
+    }
+
+    #[test]
+    fn pushes_crates_of_any_shape() {
+        // an L-shaped crate pushes the crate under its foot but not the one beside it.
+        let warehouse = WarehouseMap::parse(
+            "\
+#######
+#..@..#
+#..a..#
+#..aa.#
+#.O.O.#
+#.....#
+#######",
+        );
+        let pushed = "\
+#######
+#.....#
+#..@..#
+#..a..#
+#.Oaa.#
+#...O.#
+#######";
+        assert_eq!(after_moves(warehouse.clone(), "v"), pushed);
+        // the crate under the foot would be pushed into the wall.
+        assert_eq!(after_moves(warehouse, "vv"), pushed);
+
+        // crates of the same letter are told apart when they do not touch, and shown as usual.
+        let warehouse = WarehouseMap::parse("#######\n#@aa.a#\n#######");
+        assert_eq!(warehouse.crates.len(), 2);
+        assert_eq!(after_moves(warehouse, ">>"), "#######\n#.@[]O#\n#######");
+    }
+
+    #[test]
+    fn scales_crates() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let (map, _) = input.split_once("\n\n").unwrap();
+        let doubled = map
+            .chars()
+            .map(|c| match c {
+                '#' => "##",
+                'O' => "[]",
+                '.' => "..",
+                '@' => "@.",
+                _ => "\n",
+            })
+            .collect::<String>();
+        assert_eq!(
+            WarehouseMap::from_doubled_str(map).to_string(),
+            WarehouseMap::parse(&doubled).to_string()
+        );
+
+        let warehouse = WarehouseMap::scaled("#####\n#@O.#\n#####", 3, 2);
+        assert_eq!(
+            warehouse.to_string(),
+            "\
+###############
+###############
+###@..aaa...###
+###...aaa...###
+###############
+###############"
+        );
+        assert_eq!(warehouse.sum_gps(), 206);
+        assert_eq!(
+            after_moves(warehouse, ">>>>"),
+            "\
+###############
+###############
+###....@aaa.###
+###.....aaa.###
+###############
+###############"
+        );
+    }
+
+    #[test]
+    fn matches_shifting_boxes() {
+        Differential::new(
+            |rng| {
+                let size = rng.below(10) + 3;
+                gen::day15(rng, size)
+            },
+            |input: &String| brute_part_one(input),
+            |input: &String| part_one(input),
+        )
+        .format(Clone::clone)
+        .cases(1_000)
+        .assert();
+    }
+
+    #[test]
+    fn vertical_crates_mirror_horizontal_ones() {
+        fn transpose(input: &str) -> String {
+            let (map, moves) = input.split_once("\n\n").unwrap();
+            let rows = map.lines().map(|line| line.as_bytes()).collect_vec();
+            let map = (0..rows[0].len())
+                .map(|x| rows.iter().map(|row| row[x] as char).collect::<String>())
+                .join("\n");
+            let moves = moves
+                .chars()
+                .map(|c| match c {
+                    '^' => '<',
+                    '<' => '^',
+                    'v' => '>',
+                    '>' => 'v',
+                    c => c,
+                })
+                .collect::<String>();
+            format!("{map}\n\n{moves}")
+        }
+
+        // the cells of each crate, with x and y swapped for the vertical ones.
+        fn crates(input: &str, width: Int, height: Int, swap: bool) -> Vec<Vec<(Int, Int)>> {
+            let (map, moves) = input.split_once("\n\n").unwrap();
+            let mut warehouse = WarehouseMap::scaled(map, width, height);
+            moves.chars().filter_map(direction).for_each(|delta| {
+                warehouse.push(delta);
+            });
+            warehouse
+                .crates
+                .iter()
+                .map(|cr| {
+                    cr.cells()
+                        .map(|p| if swap { (p.y, p.x) } else { (p.x, p.y) })
+                        .sorted()
+                        .collect_vec()
+                })
+                .sorted()
+                .collect()
+        }
+
+        Differential::new(
+            |rng| {
+                let size = rng.below(10) + 3;
+                gen::day15(rng, size)
+            },
+            |input: &String| crates(input, 2, 1, false),
+            |input: &String| crates(&transpose(input), 1, 2, true),
+        )
+        .format(Clone::clone)
+        .cases(1_000)
+        .assert();
+    }
 }
@@ -0,0 +1,474 @@
+//! Random puzzle inputs for every solved day, for benchmarks, differential tests and fuzzing.
+//! Inputs follow the format of real puzzle inputs. `size` scales them (lines, grid side or length),
+//! and the same seed always produces the same input. Generators include edge cases real inputs rarely
+//! have, e.g. single-level reports (day 2), files of size 0 (day 9), a guard at the border (day 6)
+//! or collinear buttons (day 13).
+
+use std::fmt::Write;
+
+use crate::template::{rng::Rng, Day};
+
+/// Generates a puzzle input of one day.
+#[derive(Clone, Copy, Debug)]
+pub struct Generator {
+    /// Roughly the size of a real puzzle input.
+    pub default_size: usize,
+    pub generate: fn(&mut Rng, usize) -> String,
+}
+
+/// Returns the generator of `day`, or `None` if the day is not solved yet.
+pub fn generator(day: Day) -> Option<Generator> {
+    let (default_size, generate): (usize, fn(&mut Rng, usize) -> String) = match day.into_inner() {
+        1 => (1000, day01),
+        2 => (1000, day02),
+        3 => (18_000, day03),
+        4 => (140, day04),
+        5 => (200, day05),
+        6 => (130, day06),
+        7 => (850, day07),
+        8 => (50, day08),
+        9 => (19_999, day09),
+        10 => (45, day10),
+        11 => (8, day11),
+        12 => (140, day12),
+        13 => (320, day13),
+        14 => (500, day14),
+        15 => (50, day15),
+        _ => return None,
+    };
+
+    Some(Generator {
+        default_size,
+        generate,
+    })
+}
+
+/// Generates an input of `day` with the given seed, or `None` if the day has no generator.
+pub fn generate(day: Day, size: Option<usize>, seed: u64) -> Option<String> {
+    let generator = generator(day)?;
+    let size = size.unwrap_or(generator.default_size);
+    Some((generator.generate)(&mut Rng::new(seed), size))
+}
+
+fn lines(lines: impl Iterator<Item = String>) -> String {
+    lines.fold(String::new(), |mut out, line| {
+        out.push_str(&line);
+        out.push('\n');
+        out
+    })
+}
+
+fn grid(rows: &[Vec<char>]) -> String {
+    lines(rows.iter().map(|row| row.iter().collect()))
+}
+
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn number(rng: &mut Rng, min: u64, max: u64) -> u64 {
+    rng.range(min as i64..=max as i64) as u64
+}
+
+/// Two lists of location ids, with repeated ids so that similarity scores are not zero.
+pub fn day01(rng: &mut Rng, size: usize) -> String {
+    let ids: Vec<u64> = (0..size.max(1))
+        .map(|_| number(rng, 10_000, 99_999))
+        .collect();
+
+    lines((0..size).map(|_| {
+        let left = *rng.choose(&ids);
+        let right = if rng.chance(0.3) {
+            *rng.choose(&ids)
+        } else {
+            number(rng, 10_000, 99_999)
+        };
+        format!("{left}   {right}")
+    }))
+}
+
+/// Reports of up to 8 levels that change gradually, so that many are safe or safe after one removal.
+pub fn day02(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| {
+        let len = if rng.chance(0.05) {
+            1
+        } else {
+            rng.below(4) + 5
+        };
+        let direction = if rng.chance(0.5) { 1 } else { -1 };
+        let mut level = rng.range(1..=99);
+
+        (0..len)
+            .map(|_| {
+                let step = if rng.chance(0.9) {
+                    direction * rng.range(1..=3)
+                } else {
+                    rng.range(-5..=5)
+                };
+                level = (level + step).clamp(1, 99);
+                level.to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }))
+}
+
+/// Corrupted memory of about `size` characters, with valid and almost valid instructions.
+pub fn day03(rng: &mut Rng, size: usize) -> String {
+    const NOISE: &[&str] = &[
+        "mul(", "mul[", "mul ( ", ")", ",", "do(", "don't", "who()", "select()", "#", "'", "%",
+        "+", "from()", "where()", "?", "<", ">", "[", "]", "{", "}", "-", "what()", "~", "@", "^",
+    ];
+
+    let mut out = String::new();
+    while out.len() < size {
+        match rng.below(8) {
+            0..=2 => {
+                let (a, b) = (rng.range(0..=999), rng.range(0..=999));
+                write!(out, "mul({a},{b})").unwrap();
+            }
+            3 => out.push_str("do()"),
+            4 => out.push_str("don't()"),
+            5 => {
+                // too many digits, or a missing closing parenthesis.
+                let (a, b) = (rng.range(0..=9999), rng.range(0..=99));
+                write!(out, "mul({a},{b}").unwrap();
+            }
+            _ => out.push_str(rng.choose::<&str>(NOISE)),
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// A square of letters from `XMAS`.
+pub fn day04(rng: &mut Rng, size: usize) -> String {
+    let rows: Vec<Vec<char>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| *rng.choose(&['X', 'M', 'A', 'S']))
+                .collect()
+        })
+        .collect();
+    grid(&rows)
+}
+
+/// Ordering rules between every pair of pages, and `size` updates with an odd number of pages.
+pub fn day05(rng: &mut Rng, size: usize) -> String {
+    let mut pages: Vec<u64> = (10..100).collect();
+    rng.shuffle(&mut pages);
+    pages.truncate(49);
+
+    let mut rules = vec![];
+    for (i, before) in pages.iter().enumerate() {
+        for after in &pages[i + 1..] {
+            rules.push(format!("{before}|{after}"));
+        }
+    }
+    rng.shuffle(&mut rules);
+
+    let updates = (0..size).map(|_| {
+        let mut update = pages.clone();
+        rng.shuffle(&mut update);
+        update.truncate(rng.below(11) * 2 + 3);
+        if rng.chance(0.5) {
+            // in the right order.
+            update.sort_by_key(|page| pages.iter().position(|p| p == page));
+        }
+        update
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+
+    let mut out = lines(rules.into_iter());
+    out.push('\n');
+    out.push_str(&lines(updates));
+    out
+}
+
+/// A square map with obstructions and a guard facing up, sometimes at the border.
+pub fn day06(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut rows: Vec<Vec<char>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.chance(0.08) { '#' } else { '.' })
+                .collect()
+        })
+        .collect();
+
+    let (x, y) = if rng.chance(0.2) {
+        (rng.below(size), *rng.choose(&[0, size - 1]))
+    } else {
+        (rng.below(size), rng.below(size))
+    };
+    rows[y][x] = '^';
+    grid(&rows)
+}
+
+/// Equations whose test values are reachable with `+`, `*` and `||` about half of the time.
+/// Operands have at most 15 digits in total, so that any combination of operators fits into an u64.
+pub fn day07(rng: &mut Rng, size: usize) -> String {
+    lines((0..size).map(|_| {
+        let mut operands: Vec<u64> = vec![];
+        let mut digits = 0;
+        for _ in 0..rng.below(11) + 2 {
+            let max = if rng.chance(0.7) { 99 } else { 999 };
+            let operand = number(rng, 1, max);
+            let operand_digits = operand.to_string().len();
+            if digits + operand_digits > 15 {
+                break;
+            }
+            digits += operand_digits;
+            operands.push(operand);
+        }
+
+        let mut target = operands[0];
+        for &operand in &operands[1..] {
+            target = match rng.below(3) {
+                0 => target + operand,
+                1 => target * operand,
+                _ => format!("{target}{operand}").parse().unwrap(),
+            };
+        }
+        if rng.chance(0.5) {
+            target += number(rng, 1, 9);
+        }
+
+        let operands: Vec<String> = operands.iter().map(ToString::to_string).collect();
+        format!("{target}: {}", operands.join(" "))
+    }))
+}
+
+/// A square map with a few antennas per frequency.
+pub fn day08(rng: &mut Rng, size: usize) -> String {
+    const FREQUENCIES: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    let size = size.max(1);
+    let mut rows = vec![vec!['.'; size]; size];
+    let frequencies = (size * size / 40).clamp(1, FREQUENCIES.len());
+
+    for &frequency in &FREQUENCIES[..frequencies] {
+        for _ in 0..rng.below(3) + 2 {
+            rows[rng.below(size)][rng.below(size)] = char::from(frequency);
+        }
+    }
+    grid(&rows)
+}
+
+/// A disk map of `size` digits. Free space may be empty, and rarely a file has size 0.
+pub fn day09(rng: &mut Rng, size: usize) -> String {
+    let mut out: String = (0..size)
+        .map(|i| {
+            let digit = if i % 2 == 1 || rng.chance(0.01) {
+                rng.below(10)
+            } else {
+                rng.below(9) + 1
+            };
+            char::from_digit(u32::try_from(digit).unwrap(), 10).unwrap()
+        })
+        .collect();
+    out.push('\n');
+    out
+}
+
+/// A square topographic map, made of random walks uphill from trailheads so that there are trails.
+pub fn day10(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut heights: Vec<Vec<u8>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| u8::try_from(rng.below(10)).unwrap())
+                .collect()
+        })
+        .collect();
+
+    for _ in 0..size * size / 10 {
+        let (mut x, mut y) = (rng.below(size), rng.below(size));
+        for height in 0..10 {
+            heights[y][x] = height;
+            let (dx, dy) = *rng.choose(&[(0, 1), (1, 0), (0, -1), (-1, 0)]);
+            let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) else {
+                break;
+            };
+            if nx >= size || ny >= size {
+                break;
+            }
+            (x, y) = (nx, ny);
+        }
+    }
+
+    let rows: Vec<Vec<char>> = heights
+        .iter()
+        .map(|row| row.iter().map(|h| char::from(b'0' + h)).collect())
+        .collect();
+    grid(&rows)
+}
+
+/// `size` stones, including `0` and numbers with an even number of digits.
+pub fn day11(rng: &mut Rng, size: usize) -> String {
+    let stones: Vec<String> = (0..size.max(1))
+        .map(|_| match rng.below(4) {
+            0 => "0".into(),
+            _ => number(rng, 1, 9_999_999).to_string(),
+        })
+        .collect();
+    format!("{}\n", stones.join(" "))
+}
+
+/// A square garden of contiguous regions. Plants repeat, so the same plant may form several regions.
+pub fn day12(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let seeds: Vec<(usize, usize, char)> = (0..(size * size / 20).max(1))
+        .map(|_| {
+            let plant = char::from(b'A' + u8::try_from(rng.below(26)).unwrap());
+            (rng.below(size), rng.below(size), plant)
+        })
+        .collect();
+
+    let rows: Vec<Vec<char>> = (0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| {
+                    // the plant of the closest seed.
+                    seeds
+                        .iter()
+                        .min_by_key(|(sx, sy, _)| sx.abs_diff(x) + sy.abs_diff(y))
+                        .unwrap()
+                        .2
+                })
+                .collect()
+        })
+        .collect();
+    grid(&rows)
+}
+
+/// Claw machines, about half of them winnable. Some have collinear buttons.
+pub fn day13(rng: &mut Rng, size: usize) -> String {
+    let machines: Vec<String> = (0..size)
+        .map(|_| {
+            let a = (number(rng, 10, 99), number(rng, 10, 99));
+            let b = if rng.chance(0.1) {
+                let factor = number(rng, 1, 3);
+                (a.0 * factor, a.1 * factor)
+            } else {
+                (number(rng, 10, 99), number(rng, 10, 99))
+            };
+
+            let (presses_a, presses_b) = (number(rng, 0, 100), number(rng, 0, 100));
+            let mut prize = (
+                a.0 * presses_a + b.0 * presses_b,
+                a.1 * presses_a + b.1 * presses_b,
+            );
+            if rng.chance(0.5) {
+                prize.0 += number(rng, 1, 9);
+            }
+
+            format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}\n",
+                a.0, a.1, b.0, b.1, prize.0, prize.1
+            )
+        })
+        .collect();
+    machines.join("\n")
+}
+
+/// Robots in the 101 by 103 space of the real puzzle, most of which draw a tree at a random second.
+pub fn day14(rng: &mut Rng, size: usize) -> String {
+    let second = rng.below(101 * 103);
+    day14_tree(rng, size, second)
+}
+
+/// Robots in the 101 by 103 space of the real puzzle, most of which draw a tree at `second`.
+/// The others are spread over the whole space.
+pub fn day14_tree(rng: &mut Rng, size: usize, second: usize) -> String {
+    const WIDTH: i64 = 101;
+    const HEIGHT: i64 = 103;
+    const TREE_HEIGHT: i64 = 30;
+
+    let (left, top) = (
+        rng.range(0..=WIDTH - 2 * TREE_HEIGHT),
+        rng.range(0..=HEIGHT - TREE_HEIGHT),
+    );
+    let second = second as i64;
+
+    lines((0..size).map(|_| {
+        let (x, y) = if rng.chance(0.6) {
+            // a row of the triangle, and a cell in it.
+            let row = rng.range(0..=TREE_HEIGHT - 1);
+            (left + TREE_HEIGHT + rng.range(-row..=row), top + row)
+        } else {
+            (rng.range(0..=WIDTH - 1), rng.range(0..=HEIGHT - 1))
+        };
+        let (vx, vy) = (rng.range(-100..=100), rng.range(-100..=100));
+        format!(
+            "p={},{} v={vx},{vy}",
+            (x - vx * second).rem_euclid(WIDTH),
+            (y - vy * second).rem_euclid(HEIGHT),
+        )
+    }))
+}
+
+/// A square warehouse surrounded by walls, and `size * 20` moves in lines of 70.
+pub fn day15(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(3);
+    let mut rows: Vec<Vec<char>> = (0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| {
+                    if x == 0 || y == 0 || x == size - 1 || y == size - 1 || rng.chance(0.05) {
+                        '#'
+                    } else if rng.chance(0.4) {
+                        'O'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    rows[rng.below(size - 2) + 1][rng.below(size - 2) + 1] = '@';
+
+    let moves: Vec<char> = (0..size * 20)
+        .map(|_| *rng.choose(&['^', '>', 'v', '<']))
+        .collect();
+
+    let mut out = grid(&rows);
+    out.push('\n');
+    out.push_str(&lines(moves.chunks(70).map(|line| line.iter().collect())));
+    out
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, generator};
+    use crate::template::all_days;
+
+    #[test]
+    fn is_deterministic() {
+        for day in all_days().filter(|day| generator(*day).is_some()) {
+            assert_eq!(
+                generate(day, Some(20), 7),
+                generate(day, Some(20), 7),
+                "day {day}"
+            );
+        }
+    }
+
+    #[test]
+    fn generates_small_inputs() {
+        for day in all_days().filter(|day| generator(*day).is_some()) {
+            for size in 0..4 {
+                generate(day, Some(size), 1).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn scales_with_size() {
+        let day = "2".parse().unwrap();
+        assert_eq!(generate(day, Some(10), 0).unwrap().lines().count(), 10);
+        assert!(generate("16".parse().unwrap(), None, 0).is_none());
+    }
+}
@@ -1,3 +1,7 @@
+pub mod gen;
 pub mod template;
 
+#[doc(hidden)]
+pub use clap;
+
 // Use this file to add helper functions and additional modules.
@@ -1,144 +1,331 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
-use args::{parse, AppArguments};
+use advent_of_code::template::commands::solve::Profiler;
+use advent_of_code::template::commands::{
+    all, completions, config, download, fuzz, gen, inputs, read, report, scaffold, solve, stats,
+    time,
+};
+use advent_of_code::template::config::{init as init_config, Config, Overrides};
+use args::{parse, AppArguments, ConfigCommand, InputsCommand};
+use std::env;
 
 #[cfg(feature = "today")]
-use advent_of_code::template::Day;
-#[cfg(feature = "today")]
-use std::process;
+use advent_of_code::template::commands::today;
 
 mod args {
-    use advent_of_code::template::Day;
-    use std::process;
+    use advent_of_code::template::{
+        config::Overrides, report::Format, runner::RunOptions, selection::DaySelector, Day,
+    };
+    use clap::{Parser, Subcommand};
+    use clap_complete::Shell;
+    use std::path::PathBuf;
+
+    /// Solutions for Advent of Code in Rust.
+    #[derive(Parser)]
+    #[command(name = "advent_of_code", version)]
+    pub struct Cli {
+        /// Read settings from this file instead of `aoc.toml`.
+        #[arg(long, global = true, value_name = "PATH")]
+        pub config: Option<PathBuf>,
+
+        /// Puzzle year, overrides the `year` setting.
+        #[arg(long, global = true)]
+        pub year: Option<u16>,
+
+        #[command(subcommand)]
+        pub command: AppArguments,
+    }
 
+    #[derive(Subcommand)]
     pub enum AppArguments {
+        /// Download the input and puzzle description of a day via aoc-cli.
         Download {
+            /// Day number, 1 to 25.
             day: Day,
+            /// Download the input again, even if it was downloaded before.
+            #[arg(long)]
+            force: bool,
         },
+        /// Read the puzzle description of a day in the terminal. Downloads it via aoc-cli if not stored yet.
         Read {
+            /// Day number, 1 to 25.
             day: Day,
+            /// Only show one part of the puzzle.
+            #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+            part: Option<u8>,
+            /// Download the puzzle again, e.g. to add part two after solving part one.
+            #[arg(long)]
+            fetch: bool,
         },
+        /// Create the solution, input and example files for a day.
         Scaffold {
+            /// Day number, 1 to 25.
             day: Day,
+            /// Download the input and puzzle description afterwards.
+            #[arg(long)]
             download: bool,
+            /// Overwrite an existing solution file.
+            #[arg(long)]
             overwrite: bool,
         },
+        /// Run the solution of a day against its input.
         Solve {
+            /// Day number, 1 to 25.
             day: Day,
+            /// Compile with optimizations.
+            #[arg(long)]
             release: bool,
+            /// Profile heap allocations with dhat.
+            #[arg(long, conflicts_with_all = ["explain", "interactive", "render"])]
             dhat: bool,
-            submit: Option<u8>,
+            /// Sample each part in a loop and write a flamegraph to `data/profiles/DD-PART.svg`.
+            #[arg(long, conflicts_with_all = ["dhat", "submit", "explain", "interactive", "render"])]
+            profile_cpu: bool,
+            /// Count the allocations of each part.
+            #[arg(long, conflicts_with_all = ["dhat", "profile_cpu", "explain", "interactive", "render"])]
+            counts: bool,
+            #[command(flatten)]
+            options: RunOptions,
         },
+        /// Run the solutions of all (or the selected) days.
         All {
+            #[command(flatten)]
+            selector: DaySelector,
+            /// Compile with optimizations.
+            #[arg(long)]
             release: bool,
         },
+        /// Bench the solutions of days that are not fully benched yet, or of the selected days.
         Time {
+            #[command(flatten)]
+            selector: DaySelector,
+            /// Bench all days, including days with stored timings.
+            #[arg(long, conflicts_with_all = ["days", "unsolved", "changed", "slowest"])]
             all: bool,
-            day: Option<Day>,
+            /// Store the timings and update the README benchmark table.
+            #[arg(long)]
             store: bool,
+            /// Also record heap usage of every part with dhat.
+            #[arg(long)]
+            memory: bool,
+            /// Also record allocation counts of every part, and instruction counts if valgrind is installed.
+            /// Unlike timings, these do not depend on the machine.
+            #[arg(long)]
+            counts: bool,
+            /// Record counts and fail if they grew past `bench.count_tolerance` compared to the stored ones.
+            #[arg(long, conflicts_with = "store")]
+            check: bool,
+            /// Print a bar chart of the timings and write it as SVG, with a trend chart of stored runs.
+            #[arg(long)]
+            chart: bool,
+            /// Merge with the stored timings even if they were recorded in a different environment.
+            #[arg(long)]
+            force: bool,
+        },
+        /// Regenerate the marked sections of the README, or write a standalone report.
+        Report {
+            /// Write a standalone report to this file instead of updating the README.
+            #[arg(long, value_name = "PATH")]
+            output: Option<PathBuf>,
+            /// Format of the standalone report. Defaults to the extension of `--output`.
+            #[arg(long, requires = "output")]
+            format: Option<Format>,
+        },
+        /// Inspect the configuration.
+        Config {
+            #[command(subcommand)]
+            command: Option<ConfigCommand>,
+        },
+        /// Print a random puzzle input for a day.
+        Gen {
+            /// Day number, 1 to 25.
+            day: Day,
+            /// Number of lines, grid side or length of the input, depending on the day. Defaults to the size of a real input.
+            #[arg(long)]
+            size: Option<usize>,
+            /// Seed of the input. A random seed is picked and printed to stderr if omitted.
+            #[arg(long)]
+            seed: Option<u64>,
         },
+        /// Fuzz the parser and solutions of a day with `cargo fuzz`, seeding the corpus with its examples.
+        Fuzz {
+            /// Day number, 1 to 25.
+            day: Day,
+            /// Arguments passed to libFuzzer, e.g. `-max_total_time=60`.
+            #[arg(last = true)]
+            args: Vec<String>,
+        },
+        /// Show completion times, rank progression and members of a private leaderboard.
+        Stats {
+            /// Read the leaderboard JSON from this file instead of fetching `stats.leaderboard_url`.
+            #[arg(long, value_name = "PATH")]
+            file: Option<PathBuf>,
+            /// Show the times of this member, by name or id. Defaults to `stats.member`, or the owner of the leaderboard.
+            #[arg(long)]
+            member: Option<String>,
+            /// Fetch the leaderboard even if the cached one is recent.
+            #[arg(long)]
+            refresh: bool,
+        },
+        /// Verify, encrypt and decrypt puzzle inputs.
+        Inputs {
+            #[command(subcommand)]
+            command: InputsCommand,
+        },
+        /// Print a shell completion script to stdout.
+        Completions { shell: Shell },
+        /// Scaffold, download and read the puzzle of the current day.
         #[cfg(feature = "today")]
-        Today,
+        Today {
+            /// Wait until the next puzzle unlocks at midnight UTC-5, then fetch it.
+            #[arg(long)]
+            wait: bool,
+        },
     }
 
-    pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
-        let mut args = pico_args::Arguments::from_env();
+    #[derive(Subcommand)]
+    pub enum ConfigCommand {
+        /// Print the effective settings and where each one came from.
+        Show,
+    }
 
-        let app_args = match args.subcommand()?.as_deref() {
-            Some("all") => AppArguments::All {
-                release: args.contains("--release"),
-            },
-            Some("time") => {
-                let all = args.contains("--all");
-                let store = args.contains("--store");
+    #[derive(Subcommand)]
+    pub enum InputsCommand {
+        /// Check inputs against their checksums, and that no inputs or puzzles are tracked by git.
+        Check {
+            /// Record the checksums of the current inputs instead.
+            #[arg(long)]
+            update: bool,
+        },
+        /// Write an encrypted `DD.txt.enc` next to every input. The passphrase is read from `inputs.key_env`.
+        Encrypt,
+        /// Restore inputs from their encrypted copies.
+        Decrypt {
+            /// Overwrite existing inputs.
+            #[arg(long)]
+            force: bool,
+        },
+    }
 
-                AppArguments::Time {
-                    all,
-                    day: args.opt_free_from_str()?,
-                    store,
-                }
-            }
-            Some("download") => AppArguments::Download {
-                day: args.free_from_str()?,
-            },
-            Some("read") => AppArguments::Read {
-                day: args.free_from_str()?,
-            },
-            Some("scaffold") => AppArguments::Scaffold {
-                day: args.free_from_str()?,
-                download: args.contains("--download"),
-                overwrite: args.contains("--overwrite"),
-            },
-            Some("solve") => AppArguments::Solve {
-                day: args.free_from_str()?,
-                release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
-                dhat: args.contains("--dhat"),
-            },
-            #[cfg(feature = "today")]
-            Some("today") => AppArguments::Today,
-            Some(x) => {
-                eprintln!("Unknown command: {x}");
-                process::exit(1);
-            }
-            None => {
-                eprintln!("No command specified.");
-                process::exit(1);
-            }
+    /// Parse the command line. Prints usage information and exits on invalid input.
+    pub fn parse() -> (Overrides, AppArguments) {
+        let cli = Cli::parse();
+
+        let overrides = Overrides {
+            config_path: cli.config,
+            year: cli.year,
         };
 
-        let remaining = args.finish();
-        if !remaining.is_empty() {
-            eprintln!("Warning: unknown argument(s): {remaining:?}.");
-        }
+        (overrides, cli.command)
+    }
+}
 
-        Ok(app_args)
+/// Mirror global overrides to the environment, so that child invocations resolve the same configuration.
+fn export_overrides(overrides: &Overrides) {
+    if let Some(path) = &overrides.config_path {
+        env::set_var("AOC_CONFIG", path);
+    }
+    if let Some(year) = overrides.year {
+        env::set_var("AOC_YEAR", year.to_string());
     }
 }
 
 fn main() {
-    match parse() {
-        Err(err) => {
-            eprintln!("Error: {err}");
-            std::process::exit(1);
+    let (overrides, args) = parse();
+    let config = init_config(&overrides).unwrap_or_else(|err| {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    });
+    export_overrides(&overrides);
+    run(args, config);
+}
+
+fn run(args: AppArguments, config: &Config) {
+    match args {
+        AppArguments::All { selector, release } => {
+            all::handle(&selector, release || config.default_release.value);
         }
-        Ok(args) => match args {
-            AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
-            AppArguments::Download { day } => download::handle(day),
-            AppArguments::Read { day } => read::handle(day),
-            AppArguments::Scaffold {
-                day,
-                download,
-                overwrite,
-            } => {
-                scaffold::handle(day, overwrite);
-                if download {
-                    download::handle(day);
-                }
+        AppArguments::Time {
+            selector,
+            all,
+            store,
+            memory,
+            counts,
+            check,
+            chart,
+            force,
+        } => {
+            let mut profilers = vec![];
+            if memory {
+                profilers.push(Profiler::Heap);
             }
-            AppArguments::Solve {
-                day,
-                release,
-                dhat,
-                submit,
-            } => solve::handle(day, release, dhat, submit),
-            #[cfg(feature = "today")]
-            AppArguments::Today => {
-                match Day::today() {
-                    Some(day) => {
-                        scaffold::handle(day, false);
-                        download::handle(day);
-                        read::handle(day)
-                    }
-                    None => {
-                        eprintln!(
-                            "`today` command can only be run between the 1st and \
-                            the 25th of december. Please use `scaffold` with a specific day."
-                        );
-                        process::exit(1)
-                    }
-                };
+            if counts || check {
+                profilers.push(Profiler::Counts);
+            }
+            time::handle(
+                &selector,
+                all,
+                store || (config.default_store.value && !check),
+                &profilers,
+                check,
+                chart,
+                force,
+            );
+        }
+        AppArguments::Download { day, force } => download::handle(day, force),
+        AppArguments::Read { day, part, fetch } => read::handle(day, part, fetch),
+        AppArguments::Scaffold {
+            day,
+            download,
+            overwrite,
+        } => {
+            scaffold::handle(day, overwrite);
+            if download {
+                download::handle(day, false);
             }
+        }
+        AppArguments::Solve {
+            day,
+            release,
+            dhat,
+            profile_cpu,
+            counts,
+            options,
+        } => {
+            let profiler = if dhat {
+                Some(Profiler::Heap)
+            } else if profile_cpu {
+                Some(Profiler::Cpu)
+            } else if counts {
+                Some(Profiler::Counts)
+            } else {
+                None
+            };
+            solve::handle(
+                day,
+                release || config.default_release.value,
+                profiler,
+                &options,
+            );
+        }
+        AppArguments::Report { output, format } => report::handle(output, format),
+        AppArguments::Config { command } => match command {
+            Some(ConfigCommand::Show) | None => config::handle_show(config),
+        },
+        AppArguments::Gen { day, size, seed } => gen::handle(day, size, seed),
+        AppArguments::Fuzz { day, args } => fuzz::handle(day, &args),
+        AppArguments::Stats {
+            file,
+            member,
+            refresh,
+        } => stats::handle(file.as_deref(), member.as_deref(), refresh),
+        AppArguments::Inputs { command } => match command {
+            InputsCommand::Check { update } => inputs::handle_check(update),
+            InputsCommand::Encrypt => inputs::handle_encrypt(),
+            InputsCommand::Decrypt { force } => inputs::handle_decrypt(force),
         },
+        AppArguments::Completions { shell } => {
+            completions::handle::<args::Cli>(shell);
+        }
+        #[cfg(feature = "today")]
+        AppArguments::Today { wait } => today::handle(wait),
     };
 }
@@ -0,0 +1,58 @@
+/// A global allocator that counts allocations. Unlike wall-clock timings, these counts are the same
+/// on every machine, which makes them usable for regression checks in CI.
+/// Installed into solution binaries by the `count-alloc` feature.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::template::timings::Counts;
+
+#[cfg(all(feature = "count-alloc", feature = "dhat-heap"))]
+compile_error!("the `count-alloc` and `dhat-heap` features both install a global allocator and cannot be combined.");
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Forwards to the system allocator, counting every allocation and reallocation.
+pub struct CountingAlloc;
+
+fn record(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+/// Runs `func` and returns the allocations it made. Only meaningful if `CountingAlloc` is the global allocator.
+pub fn count<T>(func: impl FnOnce() -> T) -> (T, Counts) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+
+    let result = func();
+
+    let counts = Counts {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - allocated_bytes,
+        instructions: None,
+    };
+
+    (result, counts)
+}
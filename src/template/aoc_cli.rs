@@ -4,7 +4,8 @@ use std::{
     process::{Command, Output, Stdio},
 };
 
-use crate::template::Day;
+use crate::template::throttle::{self, PuzzleServer, RequestKind};
+use crate::template::{config, Day};
 
 #[derive(Debug)]
 pub enum AocCommandError {
@@ -26,73 +27,73 @@ impl Display for AocCommandError {
 }
 
 pub fn check() -> Result<(), AocCommandError> {
-    Command::new("aoc")
+    Command::new(&config::get().session_command.value)
         .arg("-V")
         .output()
         .map_err(|_| AocCommandError::CommandNotFound)?;
     Ok(())
 }
 
-pub fn read(day: Day) -> Result<Output, AocCommandError> {
+/// Downloads the input and puzzle description of `day`, overwriting existing files.
+/// With `puzzle_only`, the input is left untouched.
+pub fn download(day: Day, puzzle_only: bool) -> Result<String, AocCommandError> {
+    let input_path = get_input_path(day);
     let puzzle_path = get_puzzle_path(day);
 
-    let args = build_args(
-        "read",
-        &[
-            "--description-only".into(),
-            "--puzzle-file".into(),
-            puzzle_path,
-        ],
-        day,
-    );
-
-    call_aoc_cli(&args)
-}
+    let mut args = vec![
+        "--overwrite".into(),
+        "--input-file".into(),
+        input_path.to_string(),
+        "--puzzle-file".into(),
+        puzzle_path.to_string(),
+    ];
 
-pub fn download(day: Day) -> Result<Output, AocCommandError> {
-    let input_path = get_input_path(day);
-    let puzzle_path = get_puzzle_path(day);
+    if puzzle_only {
+        args.push("--puzzle-only".into());
+    }
 
-    let args = build_args(
-        "download",
-        &[
-            "--overwrite".into(),
-            "--input-file".into(),
-            input_path.to_string(),
-            "--puzzle-file".into(),
-            puzzle_path.to_string(),
-        ],
-        day,
-    );
-
-    let output = call_aoc_cli(&args)?;
+    let output = throttle::send(
+        &mut AocCli,
+        RequestKind::Fetch,
+        &build_args("download", &args, day),
+    )?;
     println!("---");
-    println!("🎄 Successfully wrote input to \"{}\".", &input_path);
+    if !puzzle_only {
+        println!("🎄 Successfully wrote input to \"{}\".", &input_path);
+    }
     println!("🎄 Successfully wrote puzzle to \"{}\".", &puzzle_path);
     Ok(output)
 }
 
-pub fn submit(day: Day, part: u8, result: &str) -> Result<Output, AocCommandError> {
+/// Submits an answer. Returns the response of the server, which tells whether the answer was correct.
+pub fn submit(day: Day, part: u8, result: &str) -> Result<String, AocCommandError> {
     // workaround: the argument order is inverted for submit.
     let mut args = build_args("submit", &[], day);
     args.push(part.to_string());
     args.push(result.to_string());
-    call_aoc_cli(&args)
+    throttle::send(&mut AocCli, RequestKind::Submit, &args)
 }
 
 fn get_input_path(day: Day) -> String {
-    format!("data/inputs/{day}.txt")
+    config::get()
+        .inputs_dir
+        .value
+        .join(format!("{day}.txt"))
+        .display()
+        .to_string()
 }
 
 fn get_puzzle_path(day: Day) -> String {
-    format!("data/puzzles/{day}.md")
+    config::get()
+        .puzzles_dir
+        .value
+        .join(format!("{day}.md"))
+        .display()
+        .to_string()
 }
 
 fn get_year() -> Option<u16> {
-    match std::env::var("AOC_YEAR") {
-        Ok(x) => x.parse().ok().or(None),
-        Err(_) => None,
-    }
+    config::get().year.value
 }
 
 fn build_args(command: &str, args: &[String], day: Day) -> Vec<String> {
@@ -103,22 +104,51 @@ fn build_args(command: &str, args: &[String], day: Day) -> Vec<String> {
         cmd_args.push(year.to_string());
     }
 
+    if let Some(session_file) = &config::get().session_file.value {
+        cmd_args.push("--session-file".into());
+        cmd_args.push(session_file.display().to_string());
+    }
+
     cmd_args.append(&mut vec!["--day".into(), day.to_string(), command.into()]);
 
     cmd_args
 }
 
-fn call_aoc_cli(args: &[String]) -> Result<Output, AocCommandError> {
+/// The puzzle server, reached through aoc-cli. `session.command` can point to a fake server instead.
+struct AocCli;
+
+impl PuzzleServer for AocCli {
+    type Error = AocCommandError;
+
+    fn send(&mut self, args: &[String]) -> Result<String, AocCommandError> {
+        call_aoc_cli(args)
+    }
+}
+
+/// Calls aoc-cli, echoing its output. Returns stdout so responses can be inspected.
+fn call_aoc_cli(args: &[String]) -> Result<String, AocCommandError> {
     // println!("Calling >aoc with: {}", args.join(" "));
-    let output = Command::new("aoc")
+    let mut cmd = Command::new(&config::get().session_command.value);
+
+    // aoc-cli reads the session token from `ADVENT_OF_CODE_SESSION` if set.
+    if let Some(session_env) = &config::get().session_env.value {
+        if let Ok(token) = std::env::var(session_env) {
+            cmd.env("ADVENT_OF_CODE_SESSION", token);
+        }
+    }
+
+    let output = cmd
         .args(args)
-        .stdout(Stdio::inherit())
+        .stdout(Stdio::piped())
         .stderr(Stdio::inherit())
         .output()
         .map_err(|_| AocCommandError::CommandNotCallable)?;
 
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    print!("{stdout}");
+
     if output.status.success() {
-        Ok(output)
+        Ok(stdout)
     } else {
         Err(AocCommandError::BadExitStatus(output))
     }
@@ -0,0 +1,133 @@
+/// Black and white pictures drawn by solutions, written by `--render` as PBM images or ASCII art.
+use std::path::Path;
+use std::{fs, io};
+
+/// Digits per line of a PBM image, the longest line the format allows.
+const PBM_LINE_LEN: usize = 70;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    /// A white bitmap.
+    pub fn new(width: usize, height: usize) -> Self {
+        Bitmap {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// # Panics
+    /// If the pixel is outside of the bitmap.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is outside of the bitmap"
+        );
+        self.pixels[y * self.width + x]
+    }
+
+    /// # Panics
+    /// If the pixel is outside of the bitmap.
+    pub fn set(&mut self, x: usize, y: usize, black: bool) {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is outside of the bitmap"
+        );
+        self.pixels[y * self.width + x] = black;
+    }
+
+    /// The bitmap as a plain PBM image, where `1` is black.
+    pub fn to_pbm(&self) -> String {
+        let mut pbm = format!("P1\n{} {}\n", self.width, self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            for line in row.chunks(PBM_LINE_LEN) {
+                pbm.extend(line.iter().map(|black| if *black { '1' } else { '0' }));
+                pbm.push('\n');
+            }
+        }
+        pbm
+    }
+
+    /// The bitmap with `#` for black and `.` for white pixels.
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::with_capacity((self.width + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            ascii.extend(row.iter().map(|black| if *black { '#' } else { '.' }));
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    /// Writes a PBM image if `path` ends in `.pbm`, and ASCII art otherwise. `-` prints the ASCII art.
+    pub fn write(&self, path: &Path) -> Result<(), io::Error> {
+        if path.as_os_str() == "-" {
+            print!("{}", self.to_ascii());
+            return Ok(());
+        }
+
+        let is_pbm = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pbm"));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(
+            path,
+            if is_pbm {
+                self.to_pbm()
+            } else {
+                self.to_ascii()
+            },
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::Bitmap;
+
+    fn diagonal() -> Bitmap {
+        let mut bitmap = Bitmap::new(3, 2);
+        bitmap.set(0, 0, true);
+        bitmap.set(1, 1, true);
+        bitmap
+    }
+
+    #[test]
+    fn formats_pbm() {
+        assert_eq!(diagonal().to_pbm(), "P1\n3 2\n100\n010\n");
+
+        let wide = Bitmap::new(71, 1).to_pbm();
+        let lines: Vec<&str> = wide.lines().collect();
+        assert_eq!(lines[2].len(), 70);
+        assert_eq!(lines[3], "0");
+    }
+
+    #[test]
+    fn formats_ascii() {
+        assert_eq!(diagonal().to_ascii(), "#..\n.#.\n");
+        assert_eq!(Bitmap::new(0, 0).to_ascii(), "");
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the bitmap")]
+    fn rejects_pixels_outside() {
+        diagonal().set(3, 0, true);
+    }
+}
@@ -0,0 +1,452 @@
+/// Renders benchmark timings as charts: a Unicode bar chart for the terminal, and SVG files.
+/// All charts use a logarithmic time axis, as the timings of different days span several orders of magnitude.
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{fs, io};
+
+use crate::template::config;
+use crate::template::timings::{Timing, Timings, TimingsHistory};
+
+const PART_COLORS: [&str; 2] = ["#4e79a7", "#f28e2b"];
+const TREND_COLORS: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+const PARTIAL_BLOCKS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+const MARGIN_LEFT: usize = 64;
+const MARGIN_RIGHT: usize = 16;
+const MARGIN_TOP: usize = 48;
+const MARGIN_BOTTOM: usize = 40;
+const PLOT_HEIGHT: usize = 240;
+const DAY_WIDTH: usize = 36;
+const MIN_PLOT_WIDTH: usize = 200;
+const BAR_WIDTH: usize = 14;
+const TREND_WIDTH: usize = 480;
+const LEGEND_WIDTH: usize = 80;
+
+/// A logarithmic axis spanning whole decades of nanoseconds.
+#[derive(Debug, PartialEq)]
+struct LogScale {
+    min_exp: i32,
+    max_exp: i32,
+}
+
+impl LogScale {
+    /// Returns `None` if there are no positive values.
+    #[allow(clippy::cast_possible_truncation)]
+    fn new(values: impl Iterator<Item = f64>) -> Option<Self> {
+        let (min, max) = values
+            .filter(|v| *v > 0_f64)
+            .fold((f64::MAX, f64::MIN), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+
+        if min > max {
+            return None;
+        }
+
+        let min_exp = min.log10().floor() as i32;
+        let max_exp = (max.log10().ceil() as i32).max(min_exp + 1);
+        Some(LogScale { min_exp, max_exp })
+    }
+
+    /// Position of `nanos` on the axis, from 0 to 1.
+    fn position(&self, nanos: f64) -> f64 {
+        let span = f64::from(self.max_exp - self.min_exp);
+        ((nanos.max(1_f64).log10() - f64::from(self.min_exp)) / span).clamp(0_f64, 1_f64)
+    }
+
+    /// Returns `(position, label)` for every decade on the axis.
+    fn ticks(&self) -> Vec<(f64, String)> {
+        (self.min_exp..=self.max_exp)
+            .map(|exp| {
+                let nanos = 10_f64.powi(exp);
+                (self.position(nanos), format_nanos(nanos))
+            })
+            .collect()
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn format_nanos(nanos: f64) -> String {
+    format!("{:.1?}", Duration::from_nanos(nanos as u64)).replace(".0", "")
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn part_nanos(timing: &Timing, part: u8) -> Option<f64> {
+    timing.part_nanos(part).map(|nanos| nanos as f64)
+}
+
+fn all_nanos(timings: &Timings) -> impl Iterator<Item = f64> + '_ {
+    timings
+        .data
+        .iter()
+        .flat_map(|t| [part_nanos(t, 1), part_nanos(t, 2)])
+        .flatten()
+}
+
+/// Renders a horizontal bar of `position * width` characters, with eighth-block precision.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn bar(position: f64, width: usize) -> String {
+    let eighths = ((position * (width * 8) as f64).round() as usize).max(1);
+    "█".repeat(eighths / 8) + PARTIAL_BLOCKS[eighths % 8]
+}
+
+/// Renders one bar per day and part, e.g. `Day 01 P1 ████▍      83.7µs`.
+pub fn terminal_chart(timings: &Timings, width: usize) -> String {
+    let Some(scale) = LogScale::new(all_nanos(timings)) else {
+        return "No timings to chart.".into();
+    };
+
+    let ticks = scale.ticks();
+    let mut lines = vec![format!(
+        "{:10}{:<width$} {}  (log scale)",
+        "",
+        ticks.first().map_or("", |t| &t.1),
+        ticks.last().map_or("", |t| &t.1),
+    )];
+
+    for timing in &timings.data {
+        for part in 1..=2 {
+            let label = if part == 1 {
+                format!("Day {}", timing.day)
+            } else {
+                String::new()
+            };
+
+            let (bar, value) = match part_nanos(timing, part) {
+                Some(nanos) => (bar(scale.position(nanos), width), format_nanos(nanos)),
+                None => (String::new(), "-".into()),
+            };
+
+            lines.push(format!("{label:7}P{part} {bar:<width$} {value}"));
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn svg_header(width: usize, height: usize, title: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+        viewBox=\"0 0 {width} {height}\" font-family=\"sans-serif\" font-size=\"11\">\n\
+        <rect width=\"{width}\" height=\"{height}\" fill=\"white\"/>\n\
+        <text x=\"{MARGIN_LEFT}\" y=\"20\" font-size=\"14\" font-weight=\"bold\">{title}</text>\n"
+    )
+}
+
+/// Horizontal grid lines and labels for every decade.
+#[allow(clippy::cast_precision_loss)]
+fn svg_grid(svg: &mut String, scale: &LogScale, plot_width: usize) {
+    for (position, label) in scale.ticks() {
+        let y = (MARGIN_TOP + PLOT_HEIGHT) as f64 - position * PLOT_HEIGHT as f64;
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{MARGIN_LEFT}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"#ddd\"/>\n\
+            <text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{label}</text>",
+            MARGIN_LEFT + plot_width,
+            MARGIN_LEFT - 6,
+            y + 4_f64
+        );
+    }
+}
+
+/// Renders a bar chart with a bar per day and part.
+#[allow(clippy::cast_precision_loss)]
+pub fn bar_chart_svg(timings: &Timings) -> Option<String> {
+    let scale = LogScale::new(all_nanos(timings))?;
+
+    let plot_width = (timings.data.len() * DAY_WIDTH).max(MIN_PLOT_WIDTH);
+    let width = MARGIN_LEFT + plot_width + MARGIN_RIGHT;
+    let height = MARGIN_TOP + PLOT_HEIGHT + MARGIN_BOTTOM;
+
+    let mut svg = svg_header(width, height, "Benchmarks (log scale)");
+    svg_grid(&mut svg, &scale, plot_width);
+
+    for (part, color) in PART_COLORS.iter().enumerate() {
+        let x = MARGIN_LEFT + plot_width - 120 + part * 64;
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{x}\" y=\"28\" width=\"10\" height=\"10\" fill=\"{color}\"/>\n\
+            <text x=\"{}\" y=\"37\">Part {}</text>",
+            x + 14,
+            part + 1
+        );
+    }
+
+    for (i, timing) in timings.data.iter().enumerate() {
+        let day_x = MARGIN_LEFT + i * DAY_WIDTH;
+
+        for (part, color) in PART_COLORS.iter().enumerate() {
+            let Some(nanos) = part_nanos(timing, part as u8 + 1) else {
+                continue;
+            };
+
+            let bar_height = scale.position(nanos) * PLOT_HEIGHT as f64;
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{}\" y=\"{:.1}\" width=\"{BAR_WIDTH}\" height=\"{bar_height:.1}\" fill=\"{color}\">\
+                <title>Day {} part {}: {}</title></rect>",
+                day_x + (DAY_WIDTH - 2 * BAR_WIDTH) / 2 + part * BAR_WIDTH,
+                (MARGIN_TOP + PLOT_HEIGHT) as f64 - bar_height,
+                timing.day,
+                part + 1,
+                format_nanos(nanos)
+            );
+        }
+
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            day_x + DAY_WIDTH / 2,
+            MARGIN_TOP + PLOT_HEIGHT + 16,
+            timing.day.into_inner()
+        );
+    }
+
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">Day</text>\n</svg>",
+        MARGIN_LEFT + plot_width / 2,
+        height - 8
+    );
+
+    Some(svg)
+}
+
+/// Renders the total time of every day over the stored runs, one line per day.
+/// Returns `None` if fewer than two runs were stored.
+#[allow(clippy::cast_precision_loss)]
+pub fn trend_svg(history: &TimingsHistory) -> Option<String> {
+    if history.runs.len() < 2 {
+        return None;
+    }
+
+    let scale = LogScale::new(history.runs.iter().flat_map(|run| all_nanos(&run.timings)))?;
+
+    let mut days: Vec<_> = history
+        .runs
+        .iter()
+        .flat_map(|run| run.timings.data.iter().map(|t| t.day))
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+
+    let width = MARGIN_LEFT + TREND_WIDTH + LEGEND_WIDTH + MARGIN_RIGHT;
+    let height = MARGIN_TOP + PLOT_HEIGHT + MARGIN_BOTTOM;
+    let step = TREND_WIDTH as f64 / (history.runs.len() - 1) as f64;
+
+    let mut svg = svg_header(width, height, "Benchmark trend per day (log scale)");
+    svg_grid(&mut svg, &scale, TREND_WIDTH);
+
+    for (i, day) in days.iter().enumerate() {
+        let color = TREND_COLORS[i % TREND_COLORS.len()];
+
+        let points: Vec<String> = history
+            .runs
+            .iter()
+            .enumerate()
+            .filter_map(|(run_index, run)| {
+                let timing = run.timings.get(*day)?;
+                let nanos = timing.total_nanos() as f64;
+                let x = MARGIN_LEFT as f64 + run_index as f64 * step;
+                let y =
+                    (MARGIN_TOP + PLOT_HEIGHT) as f64 - scale.position(nanos) * PLOT_HEIGHT as f64;
+                Some(format!("{x:.1},{y:.1}"))
+            })
+            .collect();
+
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"2\"><title>Day {day}</title></polyline>",
+            points.join(" ")
+        );
+
+        let legend_x = MARGIN_LEFT + TREND_WIDTH + 16;
+        let legend_y = MARGIN_TOP + i * 14;
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{legend_x}\" y=\"{legend_y}\" width=\"10\" height=\"10\" fill=\"{color}\"/>\n\
+            <text x=\"{}\" y=\"{}\">Day {}</text>",
+            legend_x + 14,
+            legend_y + 9,
+            day.into_inner()
+        );
+    }
+
+    let first = history.runs.first().map(|run| format_date(run.timestamp));
+    let last = history.runs.last().map(|run| format_date(run.timestamp));
+    let _ = writeln!(
+        svg,
+        "<text x=\"{MARGIN_LEFT}\" y=\"{y}\">{}</text>\n\
+        <text x=\"{}\" y=\"{y}\" text-anchor=\"end\">{}</text>\n</svg>",
+        first.unwrap_or_default(),
+        MARGIN_LEFT + TREND_WIDTH,
+        last.unwrap_or_default(),
+        y = MARGIN_TOP + PLOT_HEIGHT + 16,
+    );
+
+    Some(svg)
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD` (UTC).
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+#[allow(clippy::cast_possible_wrap)]
+fn format_date(timestamp: u64) -> String {
+    let z = (timestamp / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Path of the trend chart, next to the bar chart: `data/timings.svg` => `data/timings-trend.svg`.
+pub fn trend_path() -> PathBuf {
+    let path = &config::get().chart_file.value;
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "timings".into(), |s| s.to_string_lossy().to_string());
+    path.with_file_name(format!("{stem}-trend.svg"))
+}
+
+/// Writes the bar chart and, with enough stored runs, the trend chart. Returns the written files.
+pub fn write(timings: &Timings, history: &TimingsHistory) -> Result<Vec<PathBuf>, io::Error> {
+    let mut written = vec![];
+    let path = &config::get().chart_file.value;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if let Some(svg) = bar_chart_svg(timings) {
+        fs::write(path, svg)?;
+        written.push(path.clone());
+    }
+
+    if let Some(svg) = trend_svg(history) {
+        let path = trend_path();
+        fs::write(&path, svg)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{bar, bar_chart_svg, format_date, terminal_chart, trend_svg, LogScale};
+    use crate::{
+        day,
+        template::{
+            timings::{parse_duration, HistoricRun, PartTiming, Timing, Timings, TimingsHistory},
+            Day,
+        },
+    };
+
+    fn part_timing(duration: &str) -> PartTiming {
+        PartTiming {
+            nanos: parse_duration(duration).unwrap(),
+            samples: Some(10),
+        }
+    }
+
+    fn timing(day: Day, part_1: Option<&str>, part_2: Option<&str>) -> Timing {
+        Timing {
+            day,
+            part_1: part_1.map(part_timing),
+            part_2: part_2.map(part_timing),
+            source_hash: None,
+            part_1_memory: None,
+            part_2_memory: None,
+            part_1_counts: None,
+            part_2_counts: None,
+        }
+    }
+
+    fn get_mock_timings() -> Timings {
+        Timings {
+            data: vec![
+                timing(day!(1), Some("10µs"), Some("1ms")),
+                timing(day!(2), Some("100µs"), None),
+            ],
+            environment: None,
+        }
+    }
+
+    #[test]
+    fn spans_whole_decades() {
+        let scale = LogScale::new([150_f64, 2_000_000_f64].into_iter()).unwrap();
+        assert_eq!(
+            scale,
+            LogScale {
+                min_exp: 2,
+                max_exp: 7
+            }
+        );
+        assert_eq!(scale.position(100_f64), 0_f64);
+        assert_eq!(scale.position(10_000_000_f64), 1_f64);
+        assert_eq!(scale.ticks()[1].1, "1µs");
+        assert_eq!(LogScale::new([0_f64].into_iter()), None);
+    }
+
+    #[test]
+    fn renders_partial_bars() {
+        assert_eq!(bar(1_f64, 4), "████");
+        assert_eq!(bar(0.5, 3), "█▌");
+        assert_eq!(bar(0_f64, 3), "▏");
+    }
+
+    #[test]
+    fn renders_terminal_chart() {
+        let chart = terminal_chart(&get_mock_timings(), 8);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], "Day 01 P1 ▏        10µs");
+        assert_eq!(lines[2], "       P2 ████████ 1ms");
+        assert_eq!(lines[4], "       P2          -");
+    }
+
+    #[test]
+    fn renders_bar_chart_svg() {
+        let svg = bar_chart_svg(&get_mock_timings()).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<title>Day").count(), 3);
+        assert!(svg.contains("<title>Day 02 part 1: 100µs</title>"));
+        assert_eq!(bar_chart_svg(&Timings::default()), None);
+    }
+
+    #[test]
+    fn renders_trend_svg() {
+        let run = |timestamp| HistoricRun {
+            timestamp,
+            timings: get_mock_timings(),
+        };
+
+        let mut history = TimingsHistory {
+            runs: vec![run(1_733_000_000)],
+        };
+        assert_eq!(trend_svg(&history), None);
+
+        history.runs.push(run(1_734_000_000));
+        let svg = trend_svg(&history).unwrap();
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains(">2024-12-12</text>"));
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_734_000_000), "2024-12-12");
+    }
+}
@@ -1,5 +1,6 @@
-use crate::template::{all_days, run_multi::run_multi};
+use crate::template::{run_multi::run_multi, selection::DaySelector, timings::Timings};
 
-pub fn handle(is_release: bool) {
-    run_multi(&all_days().collect(), is_release, false);
+pub fn handle(selector: &DaySelector, is_release: bool) {
+    let selection = selector.select(&Timings::read_from_file());
+    run_multi(&selection, is_release, false, &[]);
 }
@@ -0,0 +1,10 @@
+use std::io;
+
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+
+pub fn handle<C: CommandFactory>(shell: Shell) {
+    let mut cmd = C::command();
+    let bin_name = cmd.get_name().to_string();
+    generate(shell, &mut cmd, bin_name, &mut io::stdout());
+}
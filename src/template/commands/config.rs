@@ -0,0 +1,25 @@
+use crate::template::{config::Config, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+pub fn handle_show(config: &Config) {
+    println!(
+        "{ANSI_BOLD}Configuration{ANSI_RESET} {ANSI_ITALIC}({}){ANSI_RESET}",
+        config.path.display()
+    );
+    println!("------");
+
+    let entries = config.entries();
+    let key_width = entries
+        .iter()
+        .map(|(key, _, _)| key.len())
+        .max()
+        .unwrap_or(0);
+    let value_width = entries
+        .iter()
+        .map(|(_, value, _)| value.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    for (key, value, source) in entries {
+        println!("{key:key_width$} = {value:value_width$}  {ANSI_ITALIC}# {source}{ANSI_RESET}");
+    }
+}
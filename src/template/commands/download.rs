@@ -1,14 +1,62 @@
-use crate::template::{aoc_cli, Day};
-use std::process;
+use crate::template::{aoc_cli, config, inputs, Day};
+use std::{fs, process};
+
+/// Downloads the input and puzzle of `day`. A non-empty input is cached and only re-downloaded with `force`.
+/// If the input is missing but an encrypted copy exists, it is decrypted instead of downloaded.
+pub fn handle(day: Day, force: bool) {
+    let config = config::get();
+    let input_path = inputs::input_path(day);
+    let puzzle_path = config.puzzles_dir.value.join(format!("{day}.md"));
+
+    if !force && !inputs::is_cached(day) && inputs::encrypted_path(day).exists() {
+        match inputs::passphrase().and_then(|key| inputs::decrypt_input(day, &key)) {
+            Ok(path) => println!("Decrypted input to \"{}\".", path.display()),
+            Err(e) => eprintln!("Failed to decrypt the stored input, downloading it: {e}"),
+        }
+    }
+
+    let is_cached = !force && inputs::is_cached(day);
+
+    if is_cached && puzzle_path.exists() {
+        println!(
+            "Input and puzzle of day {day} are already downloaded. Pass `--force` to download them again."
+        );
+        return;
+    }
 
-pub fn handle(day: Day) {
     if aoc_cli::check().is_err() {
         eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
         process::exit(1);
     }
 
-    if let Err(e) = aoc_cli::download(day) {
+    if let Err(e) = aoc_cli::download(day, is_cached) {
         eprintln!("failed to call aoc-cli: {e}");
         process::exit(1);
     };
+
+    if is_cached {
+        return;
+    }
+
+    let Ok(input) = fs::read(&input_path) else {
+        return;
+    };
+
+    if let Err(e) = inputs::record_checksum(day, &input) {
+        eprintln!("Failed to record the input checksum: {e}");
+    }
+
+    if config.inputs_encrypt.value {
+        match inputs::passphrase().and_then(|key| inputs::encrypt_input(day, &key)) {
+            Ok(path) => println!("🔒 Encrypted input to \"{}\".", path.display()),
+            Err(e) => eprintln!("Failed to encrypt the input: {e}"),
+        }
+    }
+
+    if inputs::is_ignored(&input_path) == Some(false) {
+        eprintln!(
+            "Warning: \"{}\" is not ignored by git. Puzzle inputs should not be committed, add the inputs directory to `.gitignore`.",
+            input_path.display()
+        );
+    }
 }
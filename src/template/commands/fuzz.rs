@@ -0,0 +1,128 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    process::{self, Command},
+};
+
+use crate::template::{config, Day};
+
+/// The fuzz crate, a separate workspace so that `cargo test` and `cargo clippy` do not need nightly.
+const FUZZ_DIR: &str = "fuzz";
+
+/// Fuzzes the parser and both parts of `day` with `cargo fuzz`, which requires a nightly toolchain.
+/// The corpus is seeded with the examples of the day, and crashing inputs are written to `fuzz/artifacts`.
+pub fn handle(day: Day, args: &[String]) {
+    let target = format!("day{day}");
+    let fuzz_dir = Path::new(FUZZ_DIR);
+
+    if !fuzz_dir.is_dir() {
+        eprintln!("There is no fuzz crate in \"{FUZZ_DIR}\".");
+        process::exit(1);
+    }
+    if let Err(e) = scaffold(day) {
+        eprintln!("Failed to create fuzz target: {e}");
+        process::exit(1);
+    }
+
+    let corpus = fuzz_dir.join("corpus").join(&target);
+    match seed_corpus(day, &corpus) {
+        Ok(0) => println!("No examples found for day {day}, starting from an empty corpus."),
+        Ok(seeded) => println!("Seeded the corpus with {seeded} example(s)."),
+        Err(e) => {
+            eprintln!("Failed to seed the corpus: {e}");
+            process::exit(1);
+        }
+    }
+
+    let status = Command::new("cargo")
+        .args(["+nightly", "fuzz", "run", &target])
+        .arg("--")
+        .args(args)
+        .status();
+
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!(
+                "cargo fuzz could not be called: {e}. Install it with `cargo install cargo-fuzz`."
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// Copies `data/examples/<day>*.txt` into the corpus, returning how many examples were found.
+fn seed_corpus(day: Day, corpus: &Path) -> Result<usize, std::io::Error> {
+    let config = config::get();
+    fs::create_dir_all(corpus)?;
+
+    let mut seeded = 0;
+    for entry in fs::read_dir(&config.examples_dir.value)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if name.starts_with(&day.to_string()) && name.ends_with(".txt") {
+            fs::copy(&path, corpus.join(name))?;
+            seeded += 1;
+        }
+    }
+
+    Ok(seeded)
+}
+
+/// Adds a fuzz target for `day` to the fuzz crate, if there is one.
+pub fn scaffold(day: Day) -> Result<(), std::io::Error> {
+    let fuzz_dir = Path::new(FUZZ_DIR);
+    if !fuzz_dir.is_dir() {
+        return Ok(());
+    }
+
+    let target = format!("day{day}");
+    let target_path = fuzz_dir.join("fuzz_targets").join(format!("{target}.rs"));
+    if target_path.exists() {
+        return Ok(());
+    }
+
+    let config = config::get();
+    let module_path = Path::new("../..").join(config.bin_dir.value.join(format!("{day}.rs")));
+    fs::write(
+        &target_path,
+        format!(
+            "#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = \"{}\"]
+mod solution;
+
+fuzz_target!(|input: &str| {{
+    let _ = solution::part_one(input);
+    let _ = solution::part_two(input);
+}});
+",
+            module_path.display()
+        ),
+    )?;
+
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(fuzz_dir.join("Cargo.toml"))?;
+    write!(
+        manifest,
+        "
+[[bin]]
+name = \"{target}\"
+path = \"fuzz_targets/{target}.rs\"
+test = false
+doc = false
+bench = false
+"
+    )?;
+
+    println!("Created fuzz target \"{}\"", target_path.display());
+    Ok(())
+}
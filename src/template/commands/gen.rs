@@ -0,0 +1,26 @@
+use std::{
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::gen;
+use crate::template::Day;
+
+/// Prints a random input of `day`. Without a seed, one is picked and printed to stderr so the input can be reproduced.
+pub fn handle(day: Day, size: Option<usize>, seed: Option<u64>) {
+    let seed = seed.unwrap_or_else(|| {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        eprintln!("seed: {seed}");
+        seed
+    });
+
+    match gen::generate(day, size, seed) {
+        Some(input) => print!("{input}"),
+        None => {
+            eprintln!("There is no input generator for day {day}. Add one to `src/gen.rs`.");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::{fs, process};
+
+use crate::template::inputs::{self, Checksums, Verification};
+use crate::template::{all_days, Day, ANSI_BOLD, ANSI_RESET};
+
+/// Days that have a non-empty input file.
+fn downloaded_days() -> Vec<Day> {
+    all_days().filter(|day| inputs::is_cached(*day)).collect()
+}
+
+/// Verifies input checksums and that no inputs or puzzles are tracked by git.
+/// With `update`, records the checksums of the current inputs instead of comparing them.
+pub fn handle_check(update: bool) {
+    let mut checksums = Checksums::read_from_file();
+    let mut problems = 0;
+
+    for day in downloaded_days() {
+        let path = inputs::input_path(day);
+        let Ok(input) = fs::read(&path) else {
+            continue;
+        };
+
+        if update {
+            checksums.record(day, &input);
+            continue;
+        }
+
+        match checksums.verify(day, &input) {
+            Verification::Valid => (),
+            Verification::Unrecorded => println!(
+                "\"{}\" has no recorded checksum. Run `cargo inputs check --update` to record it.",
+                path.display()
+            ),
+            Verification::Modified => {
+                problems += 1;
+                eprintln!(
+                    "{ANSI_BOLD}\"{}\" changed since it was downloaded.{ANSI_RESET} Download it again with `cargo download {day} --force`, or run `cargo inputs check --update` if the change was intended.",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    if update {
+        match checksums.store_file() {
+            Ok(()) => println!("Recorded checksums of {} inputs.", checksums.entries.len()),
+            Err(e) => {
+                eprintln!("Failed to store checksums: {e}");
+                process::exit(1);
+            }
+        }
+    }
+
+    match inputs::tracked_files() {
+        None => println!("Not a git repository, skipped checking for tracked inputs."),
+        Some(files) if files.is_empty() => (),
+        Some(files) => {
+            problems += files.len();
+            eprintln!("{ANSI_BOLD}Puzzle inputs and descriptions tracked by git:{ANSI_RESET}");
+            for file in files {
+                eprintln!("  {}", file.display());
+            }
+            eprintln!("Remove them with `git rm --cached <file>`. Encrypted inputs (`cargo inputs encrypt`) can be committed instead.");
+        }
+    }
+
+    if problems > 0 {
+        process::exit(1);
+    }
+
+    println!("Inputs are intact and not tracked by git.");
+}
+
+/// Writes an encrypted copy of every downloaded input.
+pub fn handle_encrypt() {
+    let key = passphrase();
+
+    for day in downloaded_days() {
+        match inputs::encrypt_input(day, &key) {
+            Ok(path) => println!("🔒 Encrypted input to \"{}\".", path.display()),
+            Err(e) => {
+                eprintln!("Failed to encrypt the input of day {day}: {e}");
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Restores inputs from their encrypted copies. Existing inputs are only overwritten with `force`.
+pub fn handle_decrypt(force: bool) {
+    let key = passphrase();
+
+    for day in all_days().filter(|day| inputs::encrypted_path(*day).exists()) {
+        if inputs::is_cached(day) && !force {
+            println!(
+                "Skipped day {day}, \"{}\" exists. Pass `--force` to overwrite it.",
+                inputs::input_path(day).display()
+            );
+            continue;
+        }
+
+        match inputs::decrypt_input(day, &key) {
+            Ok(path) => println!("Decrypted input to \"{}\".", path.display()),
+            Err(e) => {
+                eprintln!("Failed to decrypt the input of day {day}: {e}");
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn passphrase() -> String {
+    inputs::passphrase().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    })
+}
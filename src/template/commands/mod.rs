@@ -1,6 +1,15 @@
 pub mod all;
+pub mod completions;
+pub mod config;
 pub mod download;
+pub mod fuzz;
+pub mod gen;
+pub mod inputs;
 pub mod read;
+pub mod report;
 pub mod scaffold;
 pub mod solve;
+pub mod stats;
 pub mod time;
+#[cfg(feature = "today")]
+pub mod today;
@@ -1,15 +1,84 @@
-use std::process;
+use std::{
+    env, fs,
+    io::{stdout, IsTerminal, Write},
+    process::{self, Command, Stdio},
+};
 
-use crate::template::{aoc_cli, Day};
+use terminal_size::{terminal_size, Height, Width};
 
-pub fn handle(day: Day) {
-    if aoc_cli::check().is_err() {
-        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
-        process::exit(1);
+use crate::template::{aoc_cli, config, puzzle, Day};
+
+/// Lines are wrapped at the terminal width, but not wider than this.
+const MAX_WIDTH: usize = 100;
+
+/// Renders the stored puzzle description of `day`, optionally only one `part`.
+/// The puzzle is downloaded first if it is not stored yet, or with `fetch`, e.g. to get part two after solving part one.
+pub fn handle(day: Day, part: Option<u8>, fetch: bool) {
+    let path = config::get().puzzles_dir.value.join(format!("{day}.md"));
+
+    if fetch || !path.exists() {
+        if aoc_cli::check().is_err() {
+            eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
+            process::exit(1);
+        }
+
+        if let Err(e) = aoc_cli::download(day, true) {
+            eprintln!("failed to call aoc-cli: {e}");
+            process::exit(1);
+        };
+        println!();
     }
 
-    if let Err(e) = aoc_cli::read(day) {
-        eprintln!("failed to call aoc-cli: {e}");
+    let Ok(markdown) = fs::read_to_string(&path) else {
+        eprintln!("Failed to read the puzzle from \"{}\".", path.display());
         process::exit(1);
     };
+
+    let description = match part {
+        Some(part) => puzzle::part(&markdown, part).unwrap_or_else(|| {
+            eprintln!(
+                "Part {part} of day {day} is not stored. Solve part one, then run `cargo read {day} --fetch` to download it."
+            );
+            process::exit(1);
+        }),
+        None => &markdown,
+    };
+
+    let size = terminal_size();
+    let width = size.map_or(80, |(Width(w), _)| usize::from(w).min(MAX_WIDTH));
+    let rendered = puzzle::render(description, width);
+
+    match size {
+        Some((_, Height(h))) if stdout().is_terminal() && rendered.lines().count() >= h.into() => {
+            page(&rendered);
+        }
+        _ => print!("{rendered}"),
+    }
+}
+
+/// Shows `text` in the pager from `$PAGER`, or `less`. Prints it directly if the pager cannot be started.
+fn page(text: &str) {
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".into());
+    let mut args = pager.split_whitespace();
+
+    let child = args.next().and_then(|program| {
+        Command::new(program)
+            .args(args)
+            // keep colors, and quit if the text fits after all.
+            .env("LESS", env::var("LESS").unwrap_or_else(|_| "FRX".into()))
+            .stdin(Stdio::piped())
+            .spawn()
+            .ok()
+    });
+
+    let Some(mut child) = child else {
+        print!("{text}");
+        return;
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // the pager may be quit before reading everything.
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
 }
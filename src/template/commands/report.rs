@@ -0,0 +1,64 @@
+use std::{fs, path::PathBuf, process};
+
+use crate::template::config;
+use crate::template::report::{Format, ReportData, Section};
+
+pub fn handle(output: Option<PathBuf>, format: Option<Format>) {
+    let data = ReportData::collect();
+
+    match output {
+        Some(path) => {
+            let format = format.unwrap_or_else(|| Format::from_path(&path));
+            if let Err(e) = fs::write(&path, data.document(format)) {
+                eprintln!("Failed to write report to \"{}\": {e}", path.display());
+                process::exit(1);
+            }
+            println!("Wrote report to \"{}\".", path.display());
+        }
+        None => update_readme(&data),
+    }
+}
+
+fn update_readme(data: &ReportData) {
+    let path = &config::get().readme_file.value;
+
+    let mut readme = match fs::read_to_string(path) {
+        Ok(readme) => readme,
+        Err(e) => {
+            eprintln!("Failed to read \"{}\": {e}", path.display());
+            process::exit(1);
+        }
+    };
+
+    let updated = match data.update_readme(&mut readme) {
+        Ok(updated) => updated,
+        Err(e) => {
+            eprintln!("Failed to update \"{}\": {e:?}", path.display());
+            process::exit(1);
+        }
+    };
+
+    if updated.is_empty() {
+        eprintln!(
+            "No section markers found in \"{}\". Add a pair of markers for every section to generate, see `readme.*_marker` in `aoc.toml`.",
+            path.display()
+        );
+        process::exit(1);
+    }
+
+    if let Err(e) = fs::write(path, readme) {
+        eprintln!("Failed to write \"{}\": {e}", path.display());
+        process::exit(1);
+    }
+
+    let names: Vec<&str> = updated
+        .iter()
+        .map(|section| match section {
+            Section::Stars => "stars",
+            Section::Benchmarks => "benchmarks",
+            Section::Links => "links",
+            Section::Notes => "notes",
+        })
+        .collect();
+    println!("Updated {} in \"{}\".", names.join(", "), path.display());
+}
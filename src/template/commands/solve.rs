@@ -1,30 +1,45 @@
 use std::process::{Command, Stdio};
 
-use crate::template::Day;
+use crate::template::{runner::RunOptions, Day};
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
+/// A profiler to build and run the solution with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profiler {
+    /// Heap allocations, recorded with dhat.
+    Heap,
+    /// CPU samples, recorded with pprof and rendered as flamegraphs.
+    Cpu,
+    /// Allocation counts, recorded with a counting global allocator.
+    Counts,
+}
+
+impl Profiler {
+    /// Returns the cargo profile and feature to build the solution with.
+    pub fn cargo_args(self) -> [&'static str; 4] {
+        match self {
+            Profiler::Heap => ["--profile", "dhat", "--features", "dhat-heap"],
+            Profiler::Cpu => ["--profile", "pprof", "--features", "pprof-cpu"],
+            Profiler::Counts => ["--profile", "counts", "--features", "count-alloc"],
+        }
+    }
+}
+
+/// Runs the solution of `day` with cargo, passing `options` to the binary.
+pub fn handle(day: Day, release: bool, profiler: Option<Profiler>, options: &RunOptions) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
-    if dhat {
-        cmd_args.extend([
-            "--profile".to_string(),
-            "dhat".to_string(),
-            "--features".to_string(),
-            "dhat-heap".to_string(),
-        ]);
+    if let Some(profiler) = profiler {
+        cmd_args.extend(profiler.cargo_args().map(String::from));
     } else if release {
         cmd_args.push("--release".to_string());
     }
 
     cmd_args.push("--".to_string());
-
-    if let Some(submit_part) = submit_part {
-        cmd_args.push("--submit".to_string());
-        cmd_args.push(submit_part.to_string());
-    }
+    cmd_args.extend(options.to_args());
 
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
+        .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .spawn()
@@ -0,0 +1,176 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+    time::{Duration, SystemTime},
+};
+
+use crate::template::leaderboard::{self, Leaderboard};
+use crate::template::throttle::{self, PuzzleServer, RequestKind};
+use crate::template::{config, ANSI_BOLD, ANSI_RESET};
+
+/// Shows completion times, rank progression and a comparison of all members of a private leaderboard.
+/// The leaderboard is read from `file`, or fetched from `stats.leaderboard_url` at most every `stats.cache_minutes`.
+/// The last fetched leaderboard is cached in `paths.leaderboard`. It contains the names of other members, so it is not committed.
+pub fn handle(file: Option<&Path>, member: Option<&str>, refresh: bool) {
+    let json = match file {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("Failed to read \"{}\": {e}", path.display());
+            process::exit(1);
+        }),
+        None => cached_or_fetch(refresh),
+    };
+
+    let leaderboard: Leaderboard = json.parse().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+
+    let config = config::get();
+    let name = member.or(config.stats_member.value.as_deref());
+    let member = match name {
+        Some(name) => leaderboard.member(name).or_else(|| {
+            eprintln!("No member \"{name}\" on the leaderboard.");
+            process::exit(1);
+        }),
+        None => leaderboard.owner(),
+    };
+
+    println!(
+        "{ANSI_BOLD}Private leaderboard {} ({} members){ANSI_RESET}\n",
+        leaderboard.year,
+        leaderboard.members.len()
+    );
+
+    if let Some(member) = member {
+        println!("{ANSI_BOLD}{}{ANSI_RESET}", member.name);
+        println!("{}", leaderboard::day_table(&leaderboard, member));
+    }
+
+    print!("{}", leaderboard::member_table(&leaderboard, member));
+}
+
+/// Returns the cached leaderboard if it is recent enough, fetching it otherwise.
+/// Falls back to an outdated cache if fetching fails.
+fn cached_or_fetch(refresh: bool) -> String {
+    let config = config::get();
+    let max_age = Duration::from_secs(config.stats_cache_minutes.value * 60);
+    let cache_file = &config.leaderboard_file.value;
+    let cached = fs::read_to_string(cache_file).ok();
+
+    let age = fs::metadata(cache_file)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+
+    if let (Some(json), Some(age), false) = (&cached, age, refresh) {
+        if age < max_age {
+            println!(
+                "Using the leaderboard fetched {} ago. Pass `--refresh` to fetch it again.\n",
+                throttle::format_wait(age)
+            );
+            return json.clone();
+        }
+    }
+
+    let Some(url) = &config.stats_leaderboard_url.value else {
+        if let Some(json) = cached {
+            return json;
+        }
+        eprintln!("Set `stats.leaderboard_url` in aoc.toml, or pass a leaderboard with `--file`.");
+        process::exit(1);
+    };
+
+    match throttle::send(&mut Curl, RequestKind::Fetch, std::slice::from_ref(url)) {
+        Ok(json) => {
+            // without a valid session, the server responds with a login page instead.
+            if json.parse::<Leaderboard>().is_err() {
+                eprintln!(
+                    "The response is not a leaderboard. Check that the session token is valid."
+                );
+                process::exit(1);
+            }
+            let written = cache_file
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| fs::write(cache_file, &json));
+            if let Err(e) = written {
+                eprintln!("Failed to cache the leaderboard: {e}");
+            }
+            json
+        }
+        Err(e) => match cached {
+            Some(json) => {
+                eprintln!("Failed to fetch the leaderboard, showing the cached one: {e}\n");
+                json
+            }
+            None => {
+                eprintln!("Failed to fetch the leaderboard: {e}");
+                process::exit(1);
+            }
+        },
+    }
+}
+
+/// Fetches URLs with curl, sending the session token as a cookie.
+/// A `file://` or local URL can be configured to read from a stub instead.
+struct Curl;
+
+impl PuzzleServer for Curl {
+    type Error = String;
+
+    fn send(&mut self, args: &[String]) -> Result<String, String> {
+        let mut child = Command::new("curl")
+            .args(["--silent", "--show-error", "--fail", "--location"])
+            // options are read from stdin, so the token does not show up in the process list.
+            .args(["--config", "-"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("curl could not be called: {e}"))?;
+
+        let mut options = format!(
+            "user-agent = \"advent_of_code/{} (cargo stats)\"\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        if let Some(token) = session_token() {
+            options.push_str(&format!("cookie = \"session={token}\"\n"));
+        }
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(options.as_bytes())
+                .map_err(|e| e.to_string())?;
+        }
+
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+/// Reads the session token like aoc-cli: from `session.env`, `session.file` or `~/.adventofcode.session`.
+fn session_token() -> Option<String> {
+    let config = config::get();
+
+    if let Some(token) = config
+        .session_env
+        .value
+        .as_ref()
+        .and_then(|name| env::var(name).ok())
+    {
+        return Some(token);
+    }
+
+    let path = config.session_file.value.clone().or_else(|| {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(".adventofcode.session"))
+    })?;
+    fs::read_to_string(path)
+        .ok()
+        .map(|token| token.trim().to_string())
+}
@@ -1,32 +1,103 @@
-use std::collections::HashSet;
+use std::process;
 
+use crate::template::chart;
+use crate::template::commands::solve::Profiler;
+use crate::template::config;
+use crate::template::environment::{cargo_profile, Environment, MismatchPolicy};
+use crate::template::readme_benchmarks;
 use crate::template::run_multi::run_multi;
-use crate::template::timings::Timings;
-use crate::template::{all_days, readme_benchmarks, Day};
+use crate::template::selection::DaySelector;
+use crate::template::timings::{Timings, TimingsHistory};
+use crate::template::{ANSI_BOLD, ANSI_RESET};
 
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
+/// Timings are only taken in release builds.
+const IS_RELEASE: bool = true;
+
+pub fn handle(
+    selector: &DaySelector,
+    run_all: bool,
+    store: bool,
+    profilers: &[Profiler],
+    check: bool,
+    chart: bool,
+    force: bool,
+) {
     let stored_timings = Timings::read_from_file();
 
-    let days_to_run = day.map_or_else(
-        || {
-            if run_all {
-                all_days().collect()
-            } else {
-                // when the `--all` flag is not set, filter out days that are fully benched.
-                all_days()
-                    .filter(|day| !stored_timings.is_day_complete(*day))
-                    .collect()
+    let selection = if run_all || selector.is_filtered() {
+        selector.select(&stored_timings)
+    } else if check {
+        // when checking, default to the days that have a baseline to check against.
+        DaySelector {
+            days: Some(
+                stored_timings
+                    .data
+                    .iter()
+                    .filter(|t| t.part_1_counts.is_some() || t.part_2_counts.is_some())
+                    .map(|t| t.day)
+                    .collect(),
+            ),
+            ..selector.clone()
+        }
+        .select(&stored_timings)
+    } else {
+        // when neither `--all` nor a selector is set, filter out days that are fully benched.
+        DaySelector {
+            unsolved: true,
+            ..selector.clone()
+        }
+        .select(&stored_timings)
+    };
+
+    // only a concern if stored timings of days or parts that are not re-run are kept.
+    let replaces_stored = selection.part.is_none()
+        && stored_timings
+            .data
+            .iter()
+            .all(|t| selection.days.contains(t.day));
+
+    // stored timings are compared to the new ones when checking counts, and when merging with them.
+    let compares_stored = check || ((store || chart) && !replaces_stored);
+    if compares_stored && !force {
+        // checked before running with the profile the solutions are built with, as reported by them later.
+        let environment = Environment::capture(cargo_profile(IS_RELEASE));
+        check_environment(&stored_timings, &environment);
+    }
+
+    let mut timings = run_multi(&selection, IS_RELEASE, true, profilers).unwrap();
+
+    if check {
+        check_counts(&stored_timings, &timings);
+    }
+
+    timings.data.iter_mut().for_each(|timing| {
+        if let Some(stored) = stored_timings.get(timing.day) {
+            // keep the stored timing of a part that was not run.
+            if selection.part.is_some() {
+                *timing = timing.fill_missing_parts(stored);
             }
-        },
-        |day| HashSet::from([day]),
-    );
+            *timing = timing.fill_missing_stats(stored);
+        }
+    });
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    let merged_timings = stored_timings.merge(&timings);
 
     if store {
-        let merged_timings = stored_timings.merge(&timings);
         merged_timings.store_file().unwrap();
 
+        if config::get().bench_keep_history.value {
+            if let Err(e) = TimingsHistory::append_file(&timings) {
+                eprintln!("Failed to append run to the timings history: {e}");
+            }
+        }
+    }
+
+    // rendered after storing, so that the trend chart includes this run.
+    if chart {
+        render_charts(&merged_timings);
+    }
+
+    if store {
         println!();
         match readme_benchmarks::update(merged_timings) {
             Ok(()) => {
@@ -38,3 +109,94 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
         }
     }
 }
+
+/// Prints the timings as a bar chart and writes the SVG charts.
+fn render_charts(timings: &Timings) {
+    println!();
+    println!("{}", chart::terminal_chart(timings, 40));
+
+    match chart::write(timings, &TimingsHistory::read_from_file()) {
+        Ok(paths) => {
+            for path in paths {
+                println!("Wrote chart to \"{}\".", path.display());
+            }
+        }
+        Err(e) => eprintln!("Failed to write charts: {e}"),
+    }
+}
+
+/// Compares the current environment to the one the stored timings were recorded in.
+/// Depending on `bench.environment_mismatch`, prints the differences or exits with an error.
+fn check_environment(stored_timings: &Timings, environment: &Environment) {
+    let policy = config::get().bench_environment_mismatch.value;
+
+    let Some(stored) = &stored_timings.environment else {
+        return;
+    };
+
+    let changes = stored.changes(environment);
+    if policy == MismatchPolicy::Ignore || changes.is_empty() {
+        return;
+    }
+
+    eprintln!("{ANSI_BOLD}Stored timings were recorded in a different environment:{ANSI_RESET}");
+    for change in &changes {
+        eprintln!("  {change}");
+    }
+
+    if policy == MismatchPolicy::Refuse {
+        eprintln!("Re-run with `--all` to replace them, or pass `--force` to merge anyway.");
+        process::exit(1);
+    }
+
+    eprintln!("Merging anyway, timings in the README may not be comparable.\n");
+}
+
+/// Compares fresh counts to the stored ones and exits with an error if any of them regressed,
+/// or if there is nothing to compare them to.
+fn check_counts(stored_timings: &Timings, timings: &Timings) {
+    let tolerance = config::get().bench_count_tolerance.value;
+
+    println!();
+    if timings.data.is_empty() {
+        eprintln!(
+            "{ANSI_BOLD}Nothing to check:{ANSI_RESET} no selected day has counts to compare. \
+             Store a baseline with `cargo time --counts --store`."
+        );
+        process::exit(1);
+    }
+
+    let regressions: Vec<_> = timings
+        .data
+        .iter()
+        .filter_map(|timing| Some((timing, stored_timings.get(timing.day)?)))
+        .flat_map(|(timing, stored)| timing.count_regressions(stored, tolerance))
+        .collect();
+    let missing = timings.missing_count_baselines(stored_timings);
+
+    if regressions.is_empty() && missing.is_empty() {
+        println!("{ANSI_BOLD}No count regressions{ANSI_RESET} (tolerance {tolerance}%).");
+        return;
+    }
+
+    if !regressions.is_empty() {
+        eprintln!(
+            "{ANSI_BOLD}{} count regression(s){ANSI_RESET} (tolerance {tolerance}%):",
+            regressions.len()
+        );
+        for regression in &regressions {
+            eprintln!("  {regression}");
+        }
+    }
+
+    if !missing.is_empty() {
+        eprintln!(
+            "{ANSI_BOLD}{} part(s) without stored counts{ANSI_RESET}, store a baseline with `cargo time --counts --store`:",
+            missing.len()
+        );
+        for (day, part) in &missing {
+            eprintln!("  Day {day} part {part}");
+        }
+    }
+    process::exit(1);
+}
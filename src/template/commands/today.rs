@@ -0,0 +1,45 @@
+use std::{process, thread, time::Duration};
+
+use chrono::Utc;
+
+use crate::template::commands::{download, read, scaffold};
+use crate::template::{throttle, Day};
+
+/// Delay after midnight before fetching, so the puzzle is available on every server.
+const UNLOCK_GRACE: Duration = Duration::from_secs(2);
+
+/// Scaffolds, downloads and reads the puzzle of the current day.
+/// With `wait`, sleeps until the next puzzle unlocks first.
+pub fn handle(wait: bool) {
+    let day = if wait {
+        wait_for_unlock()
+    } else {
+        Day::today()
+    };
+
+    let Some(day) = day else {
+        eprintln!(
+            "`today` command can only be run between the 1st and \
+                the 25th of december. Please use `scaffold` with a specific day."
+        );
+        process::exit(1)
+    };
+
+    scaffold::handle(day, false);
+    download::handle(day, false);
+    read::handle(day, None, false);
+}
+
+/// Sleeps until the next puzzle unlocks at midnight on the server, and returns its day.
+fn wait_for_unlock() -> Option<Day> {
+    let (day, unlock) = Day::next_unlock(Utc::now())?;
+
+    let remaining = (unlock - Utc::now()).to_std().unwrap_or_default() + UNLOCK_GRACE;
+    println!(
+        "Day {day} unlocks in {}, waiting...",
+        throttle::format_wait(remaining)
+    );
+    thread::sleep(remaining);
+
+    Some(day)
+}
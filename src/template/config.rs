@@ -0,0 +1,708 @@
+/// Loads the settings of the template CLI from `aoc.toml`.
+///
+/// Every setting is resolved in the following order, later sources taking precedence:
+///  1. built-in default.
+///  2. `aoc.toml` (or the file pointed to by `AOC_CONFIG` / `--config`).
+///  3. environment variable, named `AOC_<KEY>` (e.g. `AOC_YEAR`, `AOC_BENCH_TARGET_MS`).
+///  4. command-line flag, if the invoked command supports one.
+use std::{
+    env,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+    sync::OnceLock,
+};
+
+use toml::{Table, Value};
+
+use crate::template::environment::MismatchPolicy;
+
+static DEFAULT_CONFIG_PATH: &str = "aoc.toml";
+static CONFIG_PATH_ENV: &str = "AOC_CONFIG";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum ConfigError {
+    IO(PathBuf, io::Error),
+    Parse(PathBuf, String),
+    UnknownKey(String),
+    InvalidValue { key: String, value: String },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::IO(path, e) => write!(f, "could not read \"{}\": {e}", path.display()),
+            ConfigError::Parse(path, e) => {
+                write!(f, "could not parse \"{}\": {e}", path.display())
+            }
+            ConfigError::UnknownKey(key) => write!(f, "unknown configuration key `{key}`."),
+            ConfigError::InvalidValue { key, value } => {
+                write!(f, "invalid value `{value}` for configuration key `{key}`.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Where the effective value of a setting came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Env(String),
+    Cli(String),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Env(name) => write!(f, "env {name}"),
+            Source::Cli(flag) => write!(f, "flag {flag}"),
+        }
+    }
+}
+
+/// A resolved setting value, together with its origin.
+#[derive(Clone, Debug)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            source: Source::Default,
+        }
+    }
+
+    /// Overrides the value with a command-line flag, if it was passed.
+    pub fn override_cli(&mut self, flag: &str, value: Option<T>) {
+        if let Some(value) = value {
+            self.value = value;
+            self.source = Source::Cli(flag.into());
+        }
+    }
+}
+
+/// Settings passed as command-line flags that apply to every command.
+#[derive(Clone, Debug, Default)]
+pub struct Overrides {
+    pub config_path: Option<PathBuf>,
+    pub year: Option<u16>,
+}
+
+/// The effective settings of the template.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub path: PathBuf,
+    pub year: Setting<Option<u16>>,
+    pub inputs_dir: Setting<PathBuf>,
+    pub examples_dir: Setting<PathBuf>,
+    pub puzzles_dir: Setting<PathBuf>,
+    pub bin_dir: Setting<PathBuf>,
+    pub timings_file: Setting<PathBuf>,
+    pub timings_history_file: Setting<PathBuf>,
+    pub readme_file: Setting<PathBuf>,
+    pub chart_file: Setting<PathBuf>,
+    pub throttle_file: Setting<PathBuf>,
+    pub leaderboard_file: Setting<PathBuf>,
+    pub session_file: Setting<Option<PathBuf>>,
+    pub session_env: Setting<Option<String>>,
+    pub session_command: Setting<String>,
+    pub bench_target_ms: Setting<u64>,
+    pub bench_min_samples: Setting<u128>,
+    pub bench_max_samples: Setting<u128>,
+    pub bench_count_tolerance: Setting<f64>,
+    pub bench_keep_history: Setting<bool>,
+    pub bench_environment_mismatch: Setting<MismatchPolicy>,
+    pub profile_duration_ms: Setting<u64>,
+    pub profile_frequency: Setting<i32>,
+    pub inputs_encrypt: Setting<bool>,
+    pub inputs_key_env: Setting<String>,
+    pub throttle_min_interval_ms: Setting<u64>,
+    pub throttle_max_retries: Setting<u32>,
+    pub stats_leaderboard_url: Setting<Option<String>>,
+    pub stats_member: Setting<Option<String>>,
+    pub stats_cache_minutes: Setting<u64>,
+    pub readme_benchmark_marker: Setting<String>,
+    pub readme_memory_columns: Setting<bool>,
+    pub readme_chart: Setting<bool>,
+    pub readme_stars_marker: Setting<String>,
+    pub readme_links_marker: Setting<String>,
+    pub readme_notes_marker: Setting<String>,
+    pub default_release: Setting<bool>,
+    pub default_store: Setting<bool>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from(DEFAULT_CONFIG_PATH),
+            year: Setting::new(None),
+            inputs_dir: Setting::new("data/inputs".into()),
+            examples_dir: Setting::new("data/examples".into()),
+            puzzles_dir: Setting::new("data/puzzles".into()),
+            bin_dir: Setting::new("src/bin".into()),
+            timings_file: Setting::new("data/timings.json".into()),
+            timings_history_file: Setting::new("data/timings-history.json".into()),
+            readme_file: Setting::new("README.md".into()),
+            chart_file: Setting::new("data/timings.svg".into()),
+            throttle_file: Setting::new("data/.aoc-throttle.json".into()),
+            leaderboard_file: Setting::new("data/leaderboard.json".into()),
+            session_file: Setting::new(None),
+            session_env: Setting::new(None),
+            session_command: Setting::new("aoc".into()),
+            bench_target_ms: Setting::new(1000),
+            bench_min_samples: Setting::new(10),
+            bench_max_samples: Setting::new(10000),
+            bench_count_tolerance: Setting::new(1.0),
+            bench_keep_history: Setting::new(false),
+            bench_environment_mismatch: Setting::new(MismatchPolicy::Warn),
+            profile_duration_ms: Setting::new(5000),
+            profile_frequency: Setting::new(999),
+            inputs_encrypt: Setting::new(false),
+            inputs_key_env: Setting::new("AOC_INPUT_KEY".into()),
+            throttle_min_interval_ms: Setting::new(5000),
+            throttle_max_retries: Setting::new(3),
+            stats_leaderboard_url: Setting::new(None),
+            stats_member: Setting::new(None),
+            stats_cache_minutes: Setting::new(15),
+            readme_benchmark_marker: Setting::new("<!--- benchmarking table --->".into()),
+            readme_memory_columns: Setting::new(false),
+            readme_chart: Setting::new(false),
+            readme_stars_marker: Setting::new("<!--- advent_readme_stars table --->".into()),
+            readme_links_marker: Setting::new("<!--- links table --->".into()),
+            readme_notes_marker: Setting::new("<!--- notes --->".into()),
+            default_release: Setting::new(false),
+            default_store: Setting::new(false),
+        }
+    }
+}
+
+impl Config {
+    /// Resolve settings from the config file, the environment and the passed overrides.
+    /// A missing config file is not an error, unless its path was set explicitly.
+    pub fn load(overrides: &Overrides) -> Result<Self, ConfigError> {
+        let (path, is_explicit) = match (&overrides.config_path, env::var(CONFIG_PATH_ENV)) {
+            (Some(path), _) => (path.clone(), true),
+            (None, Ok(path)) => (PathBuf::from(path), true),
+            (None, Err(_)) => (PathBuf::from(DEFAULT_CONFIG_PATH), false),
+        };
+
+        let table = match fs::read_to_string(&path) {
+            Ok(contents) => Table::from_str(&contents)
+                .map_err(|e| ConfigError::Parse(path.clone(), e.message().to_string()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !is_explicit => Table::new(),
+            Err(e) => return Err(ConfigError::IO(path, e)),
+        };
+
+        let mut config = Config::from_table(&table, &path, &|name| env::var(name).ok())?;
+        config.year.override_cli("--year", overrides.year.map(Some));
+        Ok(config)
+    }
+
+    fn from_table(
+        table: &Table,
+        path: &Path,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        check_keys(table)?;
+
+        let mut config = Config {
+            path: path.to_path_buf(),
+            ..Config::default()
+        };
+
+        let resolver = Resolver { table, path, env };
+        resolver.resolve_opt("year", &mut config.year)?;
+        resolver.resolve("paths.inputs", &mut config.inputs_dir)?;
+        resolver.resolve("paths.examples", &mut config.examples_dir)?;
+        resolver.resolve("paths.puzzles", &mut config.puzzles_dir)?;
+        resolver.resolve("paths.bins", &mut config.bin_dir)?;
+        resolver.resolve("paths.timings", &mut config.timings_file)?;
+        resolver.resolve("paths.timings_history", &mut config.timings_history_file)?;
+        resolver.resolve("paths.readme", &mut config.readme_file)?;
+        resolver.resolve("paths.chart", &mut config.chart_file)?;
+        resolver.resolve("paths.throttle", &mut config.throttle_file)?;
+        resolver.resolve("paths.leaderboard", &mut config.leaderboard_file)?;
+        resolver.resolve_opt("session.file", &mut config.session_file)?;
+        resolver.resolve_opt("session.env", &mut config.session_env)?;
+        resolver.resolve("session.command", &mut config.session_command)?;
+        resolver.resolve("bench.target_ms", &mut config.bench_target_ms)?;
+        resolver.resolve("bench.min_samples", &mut config.bench_min_samples)?;
+        resolver.resolve("bench.max_samples", &mut config.bench_max_samples)?;
+        resolver.resolve("bench.count_tolerance", &mut config.bench_count_tolerance)?;
+        resolver.resolve("bench.keep_history", &mut config.bench_keep_history)?;
+        resolver.resolve(
+            "bench.environment_mismatch",
+            &mut config.bench_environment_mismatch,
+        )?;
+        resolver.resolve("profile.duration_ms", &mut config.profile_duration_ms)?;
+        resolver.resolve("profile.frequency", &mut config.profile_frequency)?;
+        resolver.resolve("inputs.encrypt", &mut config.inputs_encrypt)?;
+        resolver.resolve("inputs.key_env", &mut config.inputs_key_env)?;
+        resolver.resolve(
+            "throttle.min_interval_ms",
+            &mut config.throttle_min_interval_ms,
+        )?;
+        resolver.resolve("throttle.max_retries", &mut config.throttle_max_retries)?;
+        resolver.resolve_opt("stats.leaderboard_url", &mut config.stats_leaderboard_url)?;
+        resolver.resolve_opt("stats.member", &mut config.stats_member)?;
+        resolver.resolve("stats.cache_minutes", &mut config.stats_cache_minutes)?;
+        resolver.resolve(
+            "readme.benchmark_marker",
+            &mut config.readme_benchmark_marker,
+        )?;
+        resolver.resolve("readme.memory_columns", &mut config.readme_memory_columns)?;
+        resolver.resolve("readme.chart", &mut config.readme_chart)?;
+        resolver.resolve("readme.stars_marker", &mut config.readme_stars_marker)?;
+        resolver.resolve("readme.links_marker", &mut config.readme_links_marker)?;
+        resolver.resolve("readme.notes_marker", &mut config.readme_notes_marker)?;
+        resolver.resolve("defaults.release", &mut config.default_release)?;
+        resolver.resolve("defaults.store", &mut config.default_store)?;
+
+        Ok(config)
+    }
+
+    /// Lists all settings as `(key, value, source)`, in the order they are documented.
+    pub fn entries(&self) -> Vec<(&'static str, String, &Source)> {
+        fn opt<T: Display>(value: &Option<T>) -> String {
+            value
+                .as_ref()
+                .map_or_else(|| "-".into(), ToString::to_string)
+        }
+
+        vec![
+            ("year", opt(&self.year.value), &self.year.source),
+            (
+                "paths.inputs",
+                self.inputs_dir.value.display().to_string(),
+                &self.inputs_dir.source,
+            ),
+            (
+                "paths.examples",
+                self.examples_dir.value.display().to_string(),
+                &self.examples_dir.source,
+            ),
+            (
+                "paths.puzzles",
+                self.puzzles_dir.value.display().to_string(),
+                &self.puzzles_dir.source,
+            ),
+            (
+                "paths.bins",
+                self.bin_dir.value.display().to_string(),
+                &self.bin_dir.source,
+            ),
+            (
+                "paths.timings",
+                self.timings_file.value.display().to_string(),
+                &self.timings_file.source,
+            ),
+            (
+                "paths.timings_history",
+                self.timings_history_file.value.display().to_string(),
+                &self.timings_history_file.source,
+            ),
+            (
+                "paths.readme",
+                self.readme_file.value.display().to_string(),
+                &self.readme_file.source,
+            ),
+            (
+                "paths.chart",
+                self.chart_file.value.display().to_string(),
+                &self.chart_file.source,
+            ),
+            (
+                "paths.throttle",
+                self.throttle_file.value.display().to_string(),
+                &self.throttle_file.source,
+            ),
+            (
+                "paths.leaderboard",
+                self.leaderboard_file.value.display().to_string(),
+                &self.leaderboard_file.source,
+            ),
+            (
+                "session.file",
+                opt(&self.session_file.value.as_ref().map(|p| p.display())),
+                &self.session_file.source,
+            ),
+            (
+                "session.env",
+                opt(&self.session_env.value),
+                &self.session_env.source,
+            ),
+            (
+                "session.command",
+                self.session_command.value.clone(),
+                &self.session_command.source,
+            ),
+            (
+                "bench.target_ms",
+                self.bench_target_ms.value.to_string(),
+                &self.bench_target_ms.source,
+            ),
+            (
+                "bench.min_samples",
+                self.bench_min_samples.value.to_string(),
+                &self.bench_min_samples.source,
+            ),
+            (
+                "bench.max_samples",
+                self.bench_max_samples.value.to_string(),
+                &self.bench_max_samples.source,
+            ),
+            (
+                "bench.count_tolerance",
+                self.bench_count_tolerance.value.to_string(),
+                &self.bench_count_tolerance.source,
+            ),
+            (
+                "bench.keep_history",
+                self.bench_keep_history.value.to_string(),
+                &self.bench_keep_history.source,
+            ),
+            (
+                "bench.environment_mismatch",
+                self.bench_environment_mismatch.value.to_string(),
+                &self.bench_environment_mismatch.source,
+            ),
+            (
+                "profile.duration_ms",
+                self.profile_duration_ms.value.to_string(),
+                &self.profile_duration_ms.source,
+            ),
+            (
+                "profile.frequency",
+                self.profile_frequency.value.to_string(),
+                &self.profile_frequency.source,
+            ),
+            (
+                "inputs.encrypt",
+                self.inputs_encrypt.value.to_string(),
+                &self.inputs_encrypt.source,
+            ),
+            (
+                "inputs.key_env",
+                self.inputs_key_env.value.clone(),
+                &self.inputs_key_env.source,
+            ),
+            (
+                "throttle.min_interval_ms",
+                self.throttle_min_interval_ms.value.to_string(),
+                &self.throttle_min_interval_ms.source,
+            ),
+            (
+                "throttle.max_retries",
+                self.throttle_max_retries.value.to_string(),
+                &self.throttle_max_retries.source,
+            ),
+            (
+                "stats.leaderboard_url",
+                opt(&self.stats_leaderboard_url.value),
+                &self.stats_leaderboard_url.source,
+            ),
+            (
+                "stats.member",
+                opt(&self.stats_member.value),
+                &self.stats_member.source,
+            ),
+            (
+                "stats.cache_minutes",
+                self.stats_cache_minutes.value.to_string(),
+                &self.stats_cache_minutes.source,
+            ),
+            (
+                "readme.benchmark_marker",
+                self.readme_benchmark_marker.value.clone(),
+                &self.readme_benchmark_marker.source,
+            ),
+            (
+                "readme.memory_columns",
+                self.readme_memory_columns.value.to_string(),
+                &self.readme_memory_columns.source,
+            ),
+            (
+                "readme.chart",
+                self.readme_chart.value.to_string(),
+                &self.readme_chart.source,
+            ),
+            (
+                "readme.stars_marker",
+                self.readme_stars_marker.value.clone(),
+                &self.readme_stars_marker.source,
+            ),
+            (
+                "readme.links_marker",
+                self.readme_links_marker.value.clone(),
+                &self.readme_links_marker.source,
+            ),
+            (
+                "readme.notes_marker",
+                self.readme_notes_marker.value.clone(),
+                &self.readme_notes_marker.source,
+            ),
+            (
+                "defaults.release",
+                self.default_release.value.to_string(),
+                &self.default_release.source,
+            ),
+            (
+                "defaults.store",
+                self.default_store.value.to_string(),
+                &self.default_store.source,
+            ),
+        ]
+    }
+}
+
+/// Returns the name of the environment variable overriding `key`.
+pub fn env_name(key: &str) -> String {
+    format!("AOC_{}", key.replace('.', "_").to_uppercase())
+}
+
+/// Initializes the global configuration. Has no effect if the configuration was already loaded.
+pub fn init(overrides: &Overrides) -> Result<&'static Config, ConfigError> {
+    if let Some(config) = CONFIG.get() {
+        return Ok(config);
+    }
+    let config = Config::load(overrides)?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// Returns the global configuration, loading it without overrides on first access.
+pub fn get() -> &'static Config {
+    init(&Overrides::default()).unwrap_or_else(|e| {
+        eprintln!("Failed to load configuration: {e}");
+        process::exit(1);
+    })
+}
+
+/* -------------------------------------------------------------------------- */
+
+const KNOWN_KEYS: [&str; 37] = [
+    "year",
+    "paths.inputs",
+    "paths.examples",
+    "paths.puzzles",
+    "paths.bins",
+    "paths.timings",
+    "paths.timings_history",
+    "paths.readme",
+    "paths.chart",
+    "paths.throttle",
+    "paths.leaderboard",
+    "session.file",
+    "session.env",
+    "session.command",
+    "bench.target_ms",
+    "bench.min_samples",
+    "bench.max_samples",
+    "bench.count_tolerance",
+    "bench.keep_history",
+    "bench.environment_mismatch",
+    "profile.duration_ms",
+    "profile.frequency",
+    "inputs.encrypt",
+    "inputs.key_env",
+    "throttle.min_interval_ms",
+    "throttle.max_retries",
+    "stats.leaderboard_url",
+    "stats.member",
+    "stats.cache_minutes",
+    "readme.benchmark_marker",
+    "readme.memory_columns",
+    "readme.chart",
+    "readme.stars_marker",
+    "readme.links_marker",
+    "readme.notes_marker",
+    "defaults.release",
+    "defaults.store",
+];
+
+fn check_keys(table: &Table) -> Result<(), ConfigError> {
+    for (key, value) in table {
+        match value {
+            Value::Table(section) => {
+                for sub_key in section.keys() {
+                    let full_key = format!("{key}.{sub_key}");
+                    if !KNOWN_KEYS.contains(&full_key.as_str()) {
+                        return Err(ConfigError::UnknownKey(full_key));
+                    }
+                }
+            }
+            _ if KNOWN_KEYS.contains(&key.as_str()) => (),
+            _ => return Err(ConfigError::UnknownKey(key.clone())),
+        }
+    }
+    Ok(())
+}
+
+struct Resolver<'a> {
+    table: &'a Table,
+    path: &'a Path,
+    env: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl Resolver<'_> {
+    /// Looks up the raw value of `key`, from the environment first and the config file second.
+    fn lookup(&self, key: &str) -> Result<Option<(String, Source)>, ConfigError> {
+        let env_name = env_name(key);
+        if let Some(value) = (self.env)(&env_name) {
+            return Ok(Some((value, Source::Env(env_name))));
+        }
+
+        let value = match key.split_once('.') {
+            Some((section, sub_key)) => self.table.get(section).and_then(|t| t.get(sub_key)),
+            None => self.table.get(key),
+        };
+
+        let value = match value {
+            None => return Ok(None),
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Integer(i)) => i.to_string(),
+            Some(Value::Float(f)) => f.to_string(),
+            Some(Value::Boolean(b)) => b.to_string(),
+            // arrays, tables and datetimes are not valid for any setting.
+            Some(other) => {
+                return Err(ConfigError::InvalidValue {
+                    key: key.into(),
+                    value: other.type_str().into(),
+                })
+            }
+        };
+
+        Ok(Some((value, Source::File(self.path.to_path_buf()))))
+    }
+
+    fn resolve<T: FromStr>(&self, key: &str, setting: &mut Setting<T>) -> Result<(), ConfigError> {
+        if let Some((raw, source)) = self.lookup(key)? {
+            setting.value = parse_value(key, &raw)?;
+            setting.source = source;
+        }
+        Ok(())
+    }
+
+    fn resolve_opt<T: FromStr>(
+        &self,
+        key: &str,
+        setting: &mut Setting<Option<T>>,
+    ) -> Result<(), ConfigError> {
+        if let Some((raw, source)) = self.lookup(key)? {
+            setting.value = if raw.is_empty() {
+                None
+            } else {
+                Some(parse_value(key, &raw)?)
+            };
+            setting.source = source;
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, raw: &str) -> Result<T, ConfigError> {
+    raw.parse().map_err(|_| ConfigError::InvalidValue {
+        key: key.into(),
+        value: raw.into(),
+    })
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, str::FromStr};
+
+    use toml::Table;
+
+    use super::{Config, ConfigError, Source};
+
+    fn load(toml: &str, env: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let table = Table::from_str(toml).unwrap();
+        let env = |name: &str| {
+            env.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        Config::from_table(&table, Path::new("aoc.toml"), &env)
+    }
+
+    #[test]
+    fn uses_defaults_for_empty_file() {
+        let config = load("", &[]).unwrap();
+        assert_eq!(config.year.value, None);
+        assert_eq!(config.inputs_dir.value, Path::new("data/inputs"));
+        assert_eq!(config.bench_target_ms.value, 1000);
+        assert_eq!(config.bench_target_ms.source, Source::Default);
+    }
+
+    #[test]
+    fn reads_values_from_file() {
+        let config = load(
+            "year = 2023\n[paths]\ninputs = \"inputs\"\n[bench]\nmax_samples = 50",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(config.year.value, Some(2023));
+        assert_eq!(config.year.source, Source::File("aoc.toml".into()));
+        assert_eq!(config.inputs_dir.value, Path::new("inputs"));
+        assert_eq!(config.bench_max_samples.value, 50);
+    }
+
+    #[test]
+    fn reads_state_paths_from_file() {
+        let config = load(
+            "[paths]\nthrottle = \"state/throttle.json\"\nleaderboard = \"state/leaderboard.json\"",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(config.throttle_file.value, Path::new("state/throttle.json"));
+        assert_eq!(
+            config.leaderboard_file.value,
+            Path::new("state/leaderboard.json")
+        );
+    }
+
+    #[test]
+    fn prefers_env_over_file() {
+        let config = load(
+            "year = 2023\n[defaults]\nrelease = false",
+            &[("AOC_YEAR", "2022"), ("AOC_DEFAULTS_RELEASE", "true")],
+        )
+        .unwrap();
+        assert_eq!(config.year.value, Some(2022));
+        assert_eq!(config.year.source, Source::Env("AOC_YEAR".into()));
+        assert!(config.default_release.value);
+    }
+
+    #[test]
+    fn prefers_cli_over_env() {
+        let mut config = load("", &[("AOC_YEAR", "2022")]).unwrap();
+        config.year.override_cli("--year", Some(Some(2021)));
+        assert_eq!(config.year.value, Some(2021));
+        assert_eq!(config.year.source, Source::Cli("--year".into()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn errors_on_unknown_key() {
+        load("[paths]\ninput = \"typo\"", &[]).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn errors_on_invalid_value() {
+        load("[bench]\ntarget_ms = \"soon\"", &[]).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+/// Samples a solution part with pprof and renders the result as a flamegraph.
+/// `pprof` drives its sampling with `setitimer`, so this does not need `perf` or elevated privileges.
+use std::fmt::{Display, Formatter};
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+
+use pprof::{ProfilerGuardBuilder, Report};
+
+use crate::template::{config, data_dir, Day};
+
+/// Shared libraries whose frames are skipped while sampling. Unwinding through these is unreliable.
+const BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Profiler(pprof::Error),
+    IO(io::Error),
+}
+
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::Profiler(e) => write!(f, "profiler failed: {e}"),
+            ProfileError::IO(e) => write!(f, "could not write profile: {e}"),
+        }
+    }
+}
+
+impl From<pprof::Error> for ProfileError {
+    fn from(e: pprof::Error) -> Self {
+        ProfileError::Profiler(e)
+    }
+}
+
+impl From<io::Error> for ProfileError {
+    fn from(e: io::Error) -> Self {
+        ProfileError::IO(e)
+    }
+}
+
+/// The files written for a profiled part.
+pub struct Profile {
+    pub flamegraph: PathBuf,
+    pub folded: PathBuf,
+    pub iterations: u128,
+}
+
+/// Runs `func` in a loop for the configured `profile.duration_ms` (at least once) under the sampling profiler.
+/// Writes `DD-part.svg` and `DD-part.folded` to `data/profiles`.
+pub fn profile_part<I: Clone, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    day: Day,
+    part: u8,
+) -> Result<Profile, ProfileError> {
+    let config = config::get();
+    let duration = Duration::from_millis(config.profile_duration_ms.value);
+
+    let guard = ProfilerGuardBuilder::default()
+        .frequency(config.profile_frequency.value)
+        .blocklist(&BLOCKLIST)
+        .build()?;
+
+    let timer = Instant::now();
+    let mut iterations = 0;
+
+    while iterations == 0 || timer.elapsed() < duration {
+        black_box(func(black_box(input.clone())));
+        iterations += 1;
+    }
+
+    let report = guard.report().build()?;
+    drop(guard);
+
+    let dir = data_dir("profiles");
+    fs::create_dir_all(&dir)?;
+
+    let flamegraph = dir.join(format!("{day}-{part}.svg"));
+    report.flamegraph(fs::File::create(&flamegraph)?)?;
+
+    let folded = dir.join(format!("{day}-{part}.folded"));
+    fs::write(&folded, folded_stacks(&report))?;
+
+    Ok(Profile {
+        flamegraph,
+        folded,
+        iterations,
+    })
+}
+
+/// Formats the samples in the "folded stacks" format read by `inferno` and `flamegraph.pl`:
+/// one `root;caller;callee count` line per unique stack.
+fn folded_stacks(report: &Report) -> String {
+    let mut lines: Vec<String> = report
+        .data
+        .iter()
+        .map(|(frames, count)| {
+            let mut stack = vec![frames.thread_name_or_id()];
+            stack.extend(
+                frames
+                    .frames
+                    .iter()
+                    .rev()
+                    .flat_map(|frame| frame.iter().rev().map(ToString::to_string)),
+            );
+            format!("{} {count}", stack.join(";"))
+        })
+        .collect();
+
+    lines.sort_unstable();
+    lines.push(String::new());
+    lines.join("\n")
+}
@@ -3,10 +3,10 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 #[cfg(feature = "today")]
-use chrono::{Datelike, FixedOffset, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
 
-#[cfg(feature = "today")]
-const SERVER_UTC_OFFSET: i32 = -5;
+/// Offset of the Advent of Code server from UTC in hours, puzzles unlock at its midnight.
+pub(crate) const SERVER_UTC_OFFSET: i32 = -5;
 
 /// A valid day number of advent (i.e. an integer in range 1 to 25).
 ///
@@ -55,6 +55,23 @@ impl Day {
             None
         }
     }
+
+    /// Returns the day that unlocks at the next midnight on the server after `now`, and when.
+    /// `None` if that day is not between the 1st and the 25th of december.
+    pub fn next_unlock(now: DateTime<Utc>) -> Option<(Self, DateTime<Utc>)> {
+        let offset = FixedOffset::east_opt(SERVER_UTC_OFFSET * 3600)?;
+        let tomorrow = now.with_timezone(&offset).date_naive().succ_opt()?;
+        if tomorrow.month() != 12 || tomorrow.day() > 25 {
+            return None;
+        }
+
+        let unlock = tomorrow
+            .and_hms_opt(0, 0, 0)?
+            .and_local_timezone(offset)
+            .single()?
+            .with_timezone(&Utc);
+        Some((Self::new(u8::try_from(tomorrow.day()).ok()?)?, unlock))
+    }
 }
 
 impl Display for Day {
@@ -134,6 +151,95 @@ impl Iterator for AllDays {
 
 /* -------------------------------------------------------------------------- */
 
+/// A sorted, non-duplicate set of days, parsed from a comma-separated list of
+/// day numbers and inclusive ranges (`a..b`, `a..=b`, `a..` or `..b`).
+///
+/// ```
+/// # use advent_of_code::template::Days;
+/// let days: Days = "1..3,7".parse().unwrap();
+/// assert_eq!(days.to_string(), "01,02,03,07")
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Days(Vec<Day>);
+
+impl Days {
+    /// Iterates the days in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = Day> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn contains(&self, day: Day) -> bool {
+        self.0.binary_search(&day).is_ok()
+    }
+}
+
+impl FromIterator<Day> for Days {
+    fn from_iter<T: IntoIterator<Item = Day>>(iter: T) -> Self {
+        let mut days: Vec<Day> = iter.into_iter().collect();
+        days.sort_unstable();
+        days.dedup();
+        Self(days)
+    }
+}
+
+impl Display for Days {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+        f.write_str(&days.join(","))
+    }
+}
+
+impl FromStr for Days {
+    type Err = DaysFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_day = |part: &str, default: u8| -> Result<Day, DaysFromStrError> {
+            let part = part.trim();
+            if part.is_empty() {
+                return Ok(Day(default));
+            }
+            Day::from_str(part).map_err(|_| DaysFromStrError(part.into()))
+        };
+
+        let mut days = vec![];
+
+        for part in s.split(',') {
+            match part.split_once("..") {
+                Some((start, end)) => {
+                    let start = parse_day(start, 1)?;
+                    let end = parse_day(end.strip_prefix('=').unwrap_or(end), 25)?;
+                    if start > end {
+                        return Err(DaysFromStrError(part.into()));
+                    }
+                    days.extend((start.0..=end.0).map(Day));
+                }
+                None => days
+                    .push(Day::from_str(part.trim()).map_err(|_| DaysFromStrError(part.into()))?),
+            }
+        }
+
+        Ok(days.into_iter().collect())
+    }
+}
+
+/// An error which can be returned when parsing [`Days`].
+#[derive(Debug)]
+pub struct DaysFromStrError(String);
+
+impl Error for DaysFromStrError {}
+
+impl Display for DaysFromStrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid day selection `{}`, expecting day numbers between 1 and 25 or ranges like `1..10`",
+            self.0
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
 /// Creates a [`Day`] value in a const context.
 #[macro_export]
 macro_rules! day {
@@ -152,9 +258,12 @@ macro_rules! day {
 
 /* -------------------------------------------------------------------------- */
 
-#[cfg(feature = "test_lib")]
+#[cfg(test)]
 mod tests {
-    use super::{all_days, Day};
+    use super::{all_days, Day, Days};
+    #[cfg(feature = "today")]
+    use chrono::{DateTime, Utc};
+    use proptest::prelude::*;
 
     #[test]
     fn all_days_iterator() {
@@ -187,6 +296,68 @@ mod tests {
         assert_eq!(iter.next(), Some(Day(25)));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn parses_day_lists_and_ranges() {
+        let days: Days = "3,5,7".parse().unwrap();
+        assert_eq!(days.0, vec![Day(3), Day(5), Day(7)]);
+
+        let days: Days = "1..3,2,10..=11".parse().unwrap();
+        assert_eq!(days.0, vec![Day(1), Day(2), Day(3), Day(10), Day(11)]);
+
+        let days: Days = "24..".parse().unwrap();
+        assert_eq!(days.0, vec![Day(24), Day(25)]);
+    }
+
+    #[test]
+    fn rejects_invalid_day_selections() {
+        assert!("0".parse::<Days>().is_err());
+        assert!("1..26".parse::<Days>().is_err());
+        assert!("5..3".parse::<Days>().is_err());
+        assert!("1,,2".parse::<Days>().is_err());
+        assert!("one".parse::<Days>().is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn display_round_trips(day in 1..=25_u8) {
+            let day = Day::new(day).unwrap();
+            prop_assert_eq!(day.to_string().parse::<Day>().unwrap(), day);
+            prop_assert_eq!(day.to_string().len(), 2);
+        }
+
+        #[test]
+        fn parses_only_days_in_december(n in any::<u8>()) {
+            prop_assert_eq!(n.to_string().parse::<Day>().is_ok(), (1..=25).contains(&n));
+        }
+
+        #[test]
+        fn parsed_days_round_trip(s in "\\PC{0,4}") {
+            if let Ok(day) = s.parse::<Day>() {
+                prop_assert_eq!(day.to_string().parse::<Day>().unwrap(), day);
+            }
+        }
+    }
+
+    #[cfg(feature = "today")]
+    #[test]
+    fn next_unlock_is_at_server_midnight() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(
+            Day::next_unlock(at("2024-12-05T04:58:00Z")),
+            Some((Day(5), at("2024-12-05T05:00:00Z")))
+        );
+        assert_eq!(
+            Day::next_unlock(at("2024-12-05T05:00:00Z")),
+            Some((Day(6), at("2024-12-06T05:00:00Z")))
+        );
+        assert_eq!(
+            Day::next_unlock(at("2024-11-30T20:00:00Z")),
+            Some((Day(1), at("2024-12-01T05:00:00Z")))
+        );
+        assert_eq!(Day::next_unlock(at("2024-12-25T12:00:00Z")), None);
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -0,0 +1,321 @@
+/// Differential testing: runs a reference implementation (usually brute force) and an optimized candidate
+/// on many generated inputs, and reports the first input they disagree on, minimized and in puzzle-input format.
+use std::{
+    fmt::{Debug, Display, Formatter},
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::template::rng::Rng;
+
+/// Shrinking stops after this many steps, in case shrinks do not converge.
+const MAX_SHRINK_STEPS: usize = 10_000;
+
+type Generate<'a, I> = Box<dyn Fn(&mut Rng) -> I + 'a>;
+type Run<'a, I, O> = Box<dyn Fn(&I) -> O + 'a>;
+type Shrink<'a, I> = Box<dyn Fn(&I) -> Vec<I> + 'a>;
+type Format<'a, I> = Box<dyn Fn(&I) -> String + 'a>;
+
+/// Compares a candidate implementation to a reference on generated inputs.
+///
+/// ```
+/// use advent_of_code::template::differential::{shrink, Differential};
+///
+/// Differential::new(
+///     |rng| (0..rng.below(8)).map(|_| rng.below(100) as u32).collect::<Vec<_>>(),
+///     |levels| levels.iter().copied().max(),
+///     |levels| levels.iter().copied().reduce(u32::max),
+/// )
+/// .shrink(|levels| shrink::vec(levels))
+/// .assert();
+/// ```
+pub struct Differential<'a, I, O> {
+    generate: Generate<'a, I>,
+    reference: Run<'a, I, O>,
+    candidate: Run<'a, I, O>,
+    shrink: Shrink<'a, I>,
+    format: Format<'a, I>,
+    cases: usize,
+    seed: u64,
+}
+
+/// An input on which the reference and the candidate disagree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Seed of the case that first diverged. `.seed(seed).cases(1)` reproduces it.
+    pub seed: u64,
+    pub shrink_steps: usize,
+    /// The minimized input, formatted like a puzzle input.
+    pub input: String,
+    pub reference: String,
+    pub candidate: String,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "reference and candidate differ for seed {} (shrunk in {} steps).",
+            self.seed, self.shrink_steps
+        )?;
+        writeln!(f, "--- input ---\n{}", self.input.trim_end())?;
+        writeln!(f, "--- reference ---\n{}", self.reference)?;
+        write!(f, "--- candidate ---\n{}", self.candidate)
+    }
+}
+
+impl<'a, I: Debug + 'a, O: PartialEq + Debug> Differential<'a, I, O> {
+    /// Compares `candidate` to `reference` on inputs from `generate`.
+    /// By default, 1000 cases are run, inputs are not shrunk and formatted with `Debug`.
+    pub fn new(
+        generate: impl Fn(&mut Rng) -> I + 'a,
+        reference: impl Fn(&I) -> O + 'a,
+        candidate: impl Fn(&I) -> O + 'a,
+    ) -> Self {
+        Differential {
+            generate: Box::new(generate),
+            reference: Box::new(reference),
+            candidate: Box::new(candidate),
+            shrink: Box::new(|_| vec![]),
+            format: Box::new(|input| format!("{input:?}")),
+            cases: 1000,
+            seed: 0,
+        }
+    }
+
+    /// Returns smaller variants of a diverging input, which are tried in order. See [`shrink`].
+    #[must_use]
+    pub fn shrink(mut self, shrink: impl Fn(&I) -> Vec<I> + 'a) -> Self {
+        self.shrink = Box::new(shrink);
+        self
+    }
+
+    /// Formats a diverging input, ideally like the puzzle input so it can be pasted into an example file.
+    #[must_use]
+    pub fn format(mut self, format: impl Fn(&I) -> String + 'a) -> Self {
+        self.format = Box::new(format);
+        self
+    }
+
+    #[must_use]
+    pub fn cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Seed of the first case. Every following case uses the next seed.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs both implementations, returning their outputs if they differ.
+    /// A panic counts as an output, but inputs both implementations panic on are not considered diverging.
+    fn diverges(&self, input: &I) -> Option<(Outcome<O>, Outcome<O>)> {
+        let reference = Outcome::of(&self.reference, input);
+        let candidate = Outcome::of(&self.candidate, input);
+
+        match (&reference, &candidate) {
+            (Outcome::Panicked(_), Outcome::Panicked(_)) => None,
+            _ => (reference != candidate).then_some((reference, candidate)),
+        }
+    }
+
+    /// Runs all cases, returning the first divergence after minimizing its input.
+    pub fn run(&self) -> Result<(), Divergence> {
+        for seed in self.seed..self.seed + self.cases as u64 {
+            let mut input = (self.generate)(&mut Rng::new(seed));
+            let Some(mut outputs) = self.diverges(&input) else {
+                continue;
+            };
+
+            let mut shrink_steps = 0;
+            'shrinking: while shrink_steps < MAX_SHRINK_STEPS {
+                for smaller in (self.shrink)(&input) {
+                    if let Some(smaller_outputs) = self.diverges(&smaller) {
+                        input = smaller;
+                        outputs = smaller_outputs;
+                        shrink_steps += 1;
+                        continue 'shrinking;
+                    }
+                }
+                break;
+            }
+
+            return Err(Divergence {
+                seed,
+                shrink_steps,
+                input: (self.format)(&input),
+                reference: outputs.0.to_string(),
+                candidate: outputs.1.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Runs all cases and panics with the first divergence.
+    pub fn assert(&self) {
+        if let Err(divergence) = self.run() {
+            panic!("{divergence}");
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum Outcome<O> {
+    Returned(O),
+    Panicked(String),
+}
+
+impl<O> Outcome<O> {
+    fn of<I>(run: impl Fn(&I) -> O, input: &I) -> Self {
+        match panic::catch_unwind(AssertUnwindSafe(|| run(input))) {
+            Ok(output) => Outcome::Returned(output),
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(ToString::to_string)
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                Outcome::Panicked(message)
+            }
+        }
+    }
+}
+
+impl<O: Debug> Display for Outcome<O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Returned(output) => write!(f, "{output:?}"),
+            Outcome::Panicked(message) => write!(f, "panicked: {message}"),
+        }
+    }
+}
+
+/// Common shrinks for inputs. Smaller variants come first, so shrinking converges quickly.
+pub mod shrink {
+    /// Removes chunks of halving size, then single elements.
+    pub fn vec<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+        let mut smaller = vec![];
+        let mut chunk = items.len() / 2;
+
+        while chunk > 0 {
+            for start in (0..items.len()).step_by(chunk) {
+                let mut without = items[..start].to_vec();
+                without.extend_from_slice(&items[(start + chunk).min(items.len())..]);
+                smaller.push(without);
+            }
+            chunk /= 2;
+        }
+
+        smaller
+    }
+
+    /// Removes elements like [`vec`], then shrinks single elements with `shrink_item`.
+    pub fn vec_with<T: Clone>(items: &[T], shrink_item: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+        let mut smaller = vec(items);
+
+        for (i, item) in items.iter().enumerate() {
+            for replacement in shrink_item(item) {
+                let mut changed = items.to_vec();
+                changed[i] = replacement;
+                smaller.push(changed);
+            }
+        }
+
+        smaller
+    }
+
+    /// Moves a number towards zero: zero, half of it, and one less.
+    pub fn number(n: u32) -> Vec<u32> {
+        let mut smaller = vec![0, n / 2, n.saturating_sub(1)];
+        smaller.retain(|&m| m < n);
+        smaller.dedup();
+        smaller
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{shrink, Differential};
+
+    fn numbers(rng: &mut crate::template::rng::Rng) -> Vec<u32> {
+        (0..rng.below(20)).map(|_| rng.below(50) as u32).collect()
+    }
+
+    #[test]
+    fn passes_when_equal() {
+        Differential::new(
+            numbers,
+            |v| v.iter().sum::<u32>(),
+            |v| v.iter().rev().sum::<u32>(),
+        )
+        .assert();
+    }
+
+    #[test]
+    fn reports_minimized_divergence() {
+        // the candidate ignores numbers above 40.
+        let divergence = Differential::new(
+            numbers,
+            |v| v.iter().sum::<u32>(),
+            |v| v.iter().filter(|&&n| n <= 40).sum::<u32>(),
+        )
+        .shrink(|v| shrink::vec_with(v, |&n| shrink::number(n)))
+        .format(|v| {
+            v.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .run()
+        .unwrap_err();
+
+        assert_eq!(divergence.input, "41");
+        assert_eq!(divergence.reference, "41");
+        assert_eq!(divergence.candidate, "0");
+
+        let reproduced = Differential::new(
+            numbers,
+            |v| v.iter().sum::<u32>(),
+            |v| v.iter().filter(|&&n| n <= 40).sum::<u32>(),
+        )
+        .seed(divergence.seed)
+        .cases(1)
+        .run();
+        assert!(reproduced.is_err());
+    }
+
+    #[test]
+    fn reports_panics() {
+        let divergence = Differential::new(numbers, |v| v.first().copied(), |v| Some(v[0]))
+            .shrink(|v| shrink::vec(v))
+            .run()
+            .unwrap_err();
+
+        assert_eq!(divergence.input, "[]");
+        assert!(divergence
+            .candidate
+            .starts_with("panicked: index out of bounds"));
+    }
+
+    #[test]
+    fn shrinks_vectors() {
+        assert_eq!(
+            shrink::vec(&[1, 2, 3, 4]),
+            [
+                vec![3, 4],
+                vec![1, 2],
+                vec![2, 3, 4],
+                vec![1, 3, 4],
+                vec![1, 2, 4],
+                vec![1, 2, 3]
+            ]
+        );
+        assert_eq!(shrink::number(10), [0, 5, 9]);
+        assert!(shrink::number(0).is_empty());
+    }
+}
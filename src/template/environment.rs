@@ -0,0 +1,412 @@
+/// Describes the machine and build that timings were recorded with.
+/// Timings from different environments are not comparable, so `cargo time` checks this before merging.
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{Display, Formatter},
+    fs,
+    process::Command,
+    str::FromStr,
+    thread,
+};
+
+use tinyjson::JsonValue;
+use toml::{Table, Value};
+
+/// What `cargo time` does when the stored timings were recorded in a different environment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MismatchPolicy {
+    Ignore,
+    Warn,
+    Refuse,
+}
+
+impl FromStr for MismatchPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(MismatchPolicy::Ignore),
+            "warn" => Ok(MismatchPolicy::Warn),
+            "refuse" => Ok(MismatchPolicy::Refuse),
+            other => Err(format!("unknown policy `{other}`.")),
+        }
+    }
+}
+
+impl Display for MismatchPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MismatchPolicy::Ignore => write!(f, "ignore"),
+            MismatchPolicy::Warn => write!(f, "warn"),
+            MismatchPolicy::Refuse => write!(f, "refuse"),
+        }
+    }
+}
+
+/// Environment of a benchmark run. Fields that could not be determined are `None`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Environment {
+    pub cpu_model: Option<String>,
+    pub cores: Option<usize>,
+    pub rustc: Option<String>,
+    /// Cargo profile the solutions were built with.
+    pub profile: String,
+    /// `target-cpu` and `target-feature` codegen flags from `RUSTFLAGS` and `.cargo/config.toml`.
+    pub target_flags: Vec<String>,
+    /// Default features of the crate, which solutions are built with.
+    pub features: Vec<String>,
+    pub git_commit: Option<String>,
+    pub git_dirty: bool,
+}
+
+/// A field that differs between two environments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvironmentChange {
+    pub field: &'static str,
+    pub stored: String,
+    pub current: String,
+}
+
+impl Display for EnvironmentChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.stored, self.current)
+    }
+}
+
+/// The cargo profile the solutions are built with by `cargo run`, with or without `--release`.
+pub fn cargo_profile(is_release: bool) -> &'static str {
+    if is_release {
+        "release"
+    } else {
+        "dev"
+    }
+}
+
+impl Environment {
+    /// Collects the environment of the current machine and working tree.
+    pub fn capture(profile: &str) -> Self {
+        let git_commit = command_output("git", &["rev-parse", "HEAD"]);
+        let git_dirty = git_commit.is_some()
+            && command_output("git", &["status", "--porcelain"]).is_some_and(|s| !s.is_empty());
+
+        Environment {
+            cpu_model: cpu_model(),
+            cores: thread::available_parallelism().ok().map(Into::into),
+            rustc: command_output(
+                &env::var("RUSTC").unwrap_or_else(|_| "rustc".into()),
+                &["--version"],
+            ),
+            profile: profile.into(),
+            target_flags: target_flags(),
+            features: default_features(),
+            git_commit,
+            git_dirty,
+        }
+    }
+
+    /// Lists the fields that make timings of `self` and `other` incomparable.
+    /// The git state is recorded for reference only, as changing solutions is the point of re-running them.
+    pub fn changes(&self, other: &Environment) -> Vec<EnvironmentChange> {
+        fn opt<T: ToString>(value: Option<&T>) -> String {
+            value.map_or_else(|| "unknown".into(), ToString::to_string)
+        }
+
+        fn list(values: &[String]) -> String {
+            if values.is_empty() {
+                "none".into()
+            } else {
+                values.join(" ")
+            }
+        }
+
+        let fields = [
+            (
+                "cpu",
+                opt(self.cpu_model.as_ref()),
+                opt(other.cpu_model.as_ref()),
+            ),
+            ("cores", opt(self.cores.as_ref()), opt(other.cores.as_ref())),
+            ("rustc", opt(self.rustc.as_ref()), opt(other.rustc.as_ref())),
+            ("profile", self.profile.clone(), other.profile.clone()),
+            (
+                "target flags",
+                list(&self.target_flags),
+                list(&other.target_flags),
+            ),
+            ("features", list(&self.features), list(&other.features)),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, stored, current)| stored != current)
+            .map(|(field, stored, current)| EnvironmentChange {
+                field,
+                stored,
+                current,
+            })
+            .collect()
+    }
+}
+
+impl Display for Environment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} cores), {}, {} profile",
+            self.cpu_model.as_deref().unwrap_or("unknown cpu"),
+            self.cores.map_or_else(|| "?".into(), |c| c.to_string()),
+            self.rustc.as_deref().unwrap_or("unknown rustc"),
+            self.profile
+        )?;
+
+        if let Some(commit) = &self.git_commit {
+            let short = commit.get(..7).unwrap_or(commit);
+            write!(f, ", {short}{}", if self.git_dirty { "-dirty" } else { "" })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a command and returns its trimmed stdout, if it succeeded.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn cpu_model() -> Option<String> {
+    if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") {
+        let model = cpuinfo.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "model name").then(|| value.trim().to_string())
+        });
+        if model.is_some() {
+            return model;
+        }
+    }
+
+    // macOS
+    command_output("sysctl", &["-n", "machdep.cpu.brand_string"]).filter(|s| !s.is_empty())
+}
+
+fn target_flags() -> Vec<String> {
+    let mut flags = vec![];
+
+    if let Ok(encoded) = env::var("CARGO_ENCODED_RUSTFLAGS") {
+        flags.extend(encoded.split('\x1f').map(String::from));
+    }
+
+    for name in ["RUSTFLAGS", "CARGO_BUILD_RUSTFLAGS"] {
+        if let Ok(value) = env::var(name) {
+            flags.extend(value.split_whitespace().map(String::from));
+        }
+    }
+
+    if let Some(config) = fs::read_to_string(".cargo/config.toml")
+        .ok()
+        .and_then(|s| Table::from_str(&s).ok())
+    {
+        let build = config.get("build").and_then(|b| b.get("rustflags"));
+        let targets = config
+            .get("target")
+            .and_then(Value::as_table)
+            .into_iter()
+            .flat_map(|targets| targets.values())
+            .filter_map(|target| target.get("rustflags"));
+
+        for value in build.into_iter().chain(targets) {
+            match value {
+                Value::String(s) => flags.extend(s.split_whitespace().map(String::from)),
+                Value::Array(values) => {
+                    flags.extend(values.iter().filter_map(Value::as_str).map(String::from))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut flags: Vec<String> = flags
+        .iter()
+        .map(|flag| flag.trim_start_matches("-C").to_string())
+        .filter(|flag| flag.starts_with("target-cpu=") || flag.starts_with("target-feature="))
+        .collect();
+
+    flags.sort_unstable();
+    flags.dedup();
+    flags
+}
+
+fn default_features() -> Vec<String> {
+    fs::read_to_string("Cargo.toml")
+        .ok()
+        .and_then(|s| Table::from_str(&s).ok())
+        .and_then(|manifest| {
+            let default = manifest.get("features")?.get("default")?.as_array()?;
+            Some(
+                default
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&Environment> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &Environment) -> Self {
+        fn string_array(values: &[String]) -> JsonValue {
+            JsonValue::Array(values.iter().cloned().map(JsonValue::String).collect())
+        }
+
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        if let Some(cpu_model) = &value.cpu_model {
+            map.insert("cpu_model".into(), JsonValue::String(cpu_model.clone()));
+        }
+
+        if let Some(cores) = value.cores {
+            map.insert("cores".into(), JsonValue::Number(cores as f64));
+        }
+
+        if let Some(rustc) = &value.rustc {
+            map.insert("rustc".into(), JsonValue::String(rustc.clone()));
+        }
+
+        map.insert("profile".into(), JsonValue::String(value.profile.clone()));
+        map.insert("target_flags".into(), string_array(&value.target_flags));
+        map.insert("features".into(), string_array(&value.features));
+
+        if let Some(commit) = &value.git_commit {
+            map.insert("git_commit".into(), JsonValue::String(commit.clone()));
+        }
+
+        map.insert("git_dirty".into(), JsonValue::Boolean(value.git_dirty));
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for Environment {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected environment to be a JSON object.")?;
+
+        let get_string = |key: &str| json.get(key).and_then(|v| v.get::<String>()).cloned();
+
+        let get_strings = |key: &str| -> Result<Vec<String>, String> {
+            json.get(key)
+                .and_then(|v| v.get::<Vec<JsonValue>>())
+                .ok_or(format!("Expected environment.{key} to be an array."))?
+                .iter()
+                .map(|v| {
+                    v.get::<String>()
+                        .cloned()
+                        .ok_or(format!("Expected environment.{key} to contain strings."))
+                })
+                .collect()
+        };
+
+        Ok(Environment {
+            cpu_model: get_string("cpu_model"),
+            cores: json
+                .get("cores")
+                .and_then(|v| v.get::<f64>())
+                .map(|v| *v as usize),
+            rustc: get_string("rustc"),
+            profile: get_string("profile").ok_or("Expected environment.profile to be a string.")?,
+            target_flags: get_strings("target_flags")?,
+            features: get_strings("features")?,
+            git_commit: get_string("git_commit"),
+            git_dirty: json
+                .get("git_dirty")
+                .and_then(|v| v.get::<bool>().copied())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use tinyjson::JsonValue;
+
+    use super::{Environment, MismatchPolicy};
+
+    fn environment() -> Environment {
+        Environment {
+            cpu_model: Some("AMD Ryzen 7 5800X 8-Core Processor".into()),
+            cores: Some(16),
+            rustc: Some("rustc 1.83.0 (90b35a623 2024-11-26)".into()),
+            profile: "release".into(),
+            target_flags: vec!["target-cpu=native".into()],
+            features: vec![],
+            git_commit: Some("eb31802c0ffee".into()),
+            git_dirty: false,
+        }
+    }
+
+    #[test]
+    fn ignores_git_state_in_changes() {
+        let stored = environment();
+        let current = Environment {
+            git_commit: Some("1b9cda2".into()),
+            git_dirty: true,
+            ..environment()
+        };
+        assert!(stored.changes(&current).is_empty());
+    }
+
+    #[test]
+    fn lists_changes() {
+        let stored = environment();
+        let current = Environment {
+            cores: None,
+            target_flags: vec![],
+            ..environment()
+        };
+
+        let changes: Vec<String> = stored
+            .changes(&current)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "cores: 16 -> unknown",
+                "target flags: target-cpu=native -> none"
+            ]
+        );
+    }
+
+    #[test]
+    fn roundtrips_json() {
+        let environment = Environment {
+            rustc: None,
+            git_dirty: true,
+            ..environment()
+        };
+        let json = JsonValue::from(&environment);
+        assert_eq!(Environment::try_from(&json).unwrap(), environment);
+    }
+
+    #[test]
+    fn parses_policies() {
+        assert_eq!("refuse".parse(), Ok(MismatchPolicy::Refuse));
+        assert_eq!(MismatchPolicy::Warn.to_string(), "warn");
+        assert!("fail".parse::<MismatchPolicy>().is_err());
+    }
+}
@@ -0,0 +1,364 @@
+/// Safeguards for puzzle inputs, which are not meant to be shared:
+///  1. checksums in `SHA256SUMS` to detect inputs that were edited after downloading them.
+///  2. checks that inputs and puzzle descriptions are not tracked by git.
+///  3. optional encryption, so that `DD.txt.enc` files can be committed instead of the inputs.
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::{Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use sha2::{Digest, Sha256};
+
+use crate::template::{config, Day};
+
+const CHECKSUMS_FILE: &str = "SHA256SUMS";
+const ENCRYPTED_EXTENSION: &str = "enc";
+const MAGIC: &[u8] = b"aoc-input-v1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug)]
+pub enum InputError {
+    IO(io::Error),
+    MissingKey(String),
+    KeyDerivation(String),
+    Encrypt,
+    Malformed(PathBuf),
+    Decrypt(PathBuf),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::IO(e) => write!(f, "{e}"),
+            InputError::MissingKey(name) => write!(
+                f,
+                "environment variable `{name}` with the input passphrase is not set."
+            ),
+            InputError::KeyDerivation(e) => write!(f, "could not derive key: {e}"),
+            InputError::Encrypt => write!(f, "could not encrypt input."),
+            InputError::Malformed(path) => {
+                write!(f, "\"{}\" is not an encrypted input.", path.display())
+            }
+            InputError::Decrypt(path) => write!(
+                f,
+                "could not decrypt \"{}\", the passphrase is wrong or the file was modified.",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(e: io::Error) -> Self {
+        InputError::IO(e)
+    }
+}
+
+pub fn input_path(day: Day) -> PathBuf {
+    config::get().inputs_dir.value.join(format!("{day}.txt"))
+}
+
+pub fn encrypted_path(day: Day) -> PathBuf {
+    input_path(day).with_extension(format!("txt.{ENCRYPTED_EXTENSION}"))
+}
+
+/// Returns `true` if the input of `day` was downloaded. Scaffolding creates an empty input file, which does not count.
+pub fn is_cached(day: Day) -> bool {
+    fs::metadata(input_path(day)).is_ok_and(|m| m.len() > 0)
+}
+
+/// Hex-encoded SHA-256 of `contents`.
+pub fn checksum(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Checksums of downloaded inputs, stored in the `sha256sum` format so they can also be verified with `sha256sum -c`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Checksums {
+    /// File name to hex-encoded checksum.
+    pub entries: BTreeMap<String, String>,
+}
+
+/// Result of comparing an input to its recorded checksum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verification {
+    Unrecorded,
+    Valid,
+    Modified,
+}
+
+impl Checksums {
+    fn path() -> PathBuf {
+        config::get().inputs_dir.value.join(CHECKSUMS_FILE)
+    }
+
+    /// Reads the checksums file. If not present, returns no checksums.
+    pub fn read_from_file() -> Self {
+        fs::read_to_string(Self::path())
+            .map(|s| Self::parse(&s))
+            .unwrap_or_default()
+    }
+
+    pub fn store_file(&self) -> Result<(), io::Error> {
+        fs::write(Self::path(), self.to_string())
+    }
+
+    fn parse(s: &str) -> Self {
+        let entries = s
+            .lines()
+            .filter_map(|line| {
+                let (hash, name) = line.split_once(char::is_whitespace)?;
+                // `sha256sum` marks binary mode with a leading `*`.
+                let name = name.trim_start().trim_start_matches('*');
+                Some((name.to_string(), hash.to_string()))
+            })
+            .collect();
+
+        Checksums { entries }
+    }
+
+    pub fn record(&mut self, day: Day, contents: &[u8]) {
+        self.entries
+            .insert(format!("{day}.txt"), checksum(contents));
+    }
+
+    pub fn verify(&self, day: Day, contents: &[u8]) -> Verification {
+        match self.entries.get(&format!("{day}.txt")) {
+            None => Verification::Unrecorded,
+            Some(hash) if *hash == checksum(contents) => Verification::Valid,
+            Some(_) => Verification::Modified,
+        }
+    }
+}
+
+impl Display for Checksums {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (name, hash) in &self.entries {
+            writeln!(f, "{hash}  {name}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the checksum of a freshly written input.
+pub fn record_checksum(day: Day, contents: &[u8]) -> Result<(), io::Error> {
+    let mut checksums = Checksums::read_from_file();
+    checksums.record(day, contents);
+    checksums.store_file()
+}
+
+/// Prints a warning if the input of `day` differs from the checksum recorded when it was downloaded.
+pub fn warn_if_modified(day: Day, contents: &str) {
+    if Checksums::read_from_file().verify(day, contents.as_bytes()) == Verification::Modified {
+        eprintln!(
+            "Warning: input \"{}\" changed since it was downloaded. Run `cargo inputs check` for details.",
+            input_path(day).display()
+        );
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Lists files in the inputs and puzzles directories that are tracked by git.
+/// Encrypted inputs, checksums and `.keep` files are safe to commit and not listed.
+/// Returns `None` if git is not available or this is not a repository.
+pub fn tracked_files() -> Option<Vec<PathBuf>> {
+    let config = config::get();
+    let output = Command::new("git")
+        .args(["ls-files", "-z", "--"])
+        .arg(&config.inputs_dir.value)
+        .arg(&config.puzzles_dir.value)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let files = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(PathBuf::from)
+        .filter(|path| !is_safe_to_commit(path))
+        .collect();
+
+    Some(files)
+}
+
+fn is_safe_to_commit(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    name == ".keep"
+        || name == CHECKSUMS_FILE
+        || path
+            .extension()
+            .is_some_and(|ext| ext == ENCRYPTED_EXTENSION)
+}
+
+/// Returns `Some(false)` if git would pick up `path` as an untracked file.
+/// Returns `None` if git is not available or this is not a repository.
+pub fn is_ignored(path: &Path) -> Option<bool> {
+    let status = Command::new("git")
+        .args(["check-ignore", "--quiet", "--"])
+        .arg(path)
+        .status()
+        .ok()?;
+
+    // exit code 1 means "not ignored", everything else is an error.
+    match status.code() {
+        Some(0) => Some(true),
+        Some(1) => Some(false),
+        _ => None,
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Reads the passphrase from the environment variable named by `inputs.key_env`.
+pub fn passphrase() -> Result<String, InputError> {
+    let name = &config::get().inputs_key_env.value;
+    env::var(name)
+        .ok()
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| InputError::MissingKey(name.clone()))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], InputError> {
+    let mut key = [0_u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| InputError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+/// Encrypts `plaintext` with a key derived from `passphrase`.
+/// The output starts with a format marker, followed by the random salt and nonce.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, InputError> {
+    let mut salt = [0_u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, &salt)?.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| InputError::Encrypt)?;
+
+    Ok([MAGIC, &salt, &nonce, &ciphertext].concat())
+}
+
+/// Decrypts data written by [`encrypt`]. `path` is only used in errors.
+pub fn decrypt(data: &[u8], passphrase: &str, path: &Path) -> Result<Vec<u8>, InputError> {
+    let rest = data
+        .strip_prefix(MAGIC)
+        .filter(|rest| rest.len() >= SALT_LEN + NONCE_LEN)
+        .ok_or_else(|| InputError::Malformed(path.to_path_buf()))?;
+
+    let (salt, rest) = rest.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, salt)?.into());
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| InputError::Decrypt(path.to_path_buf()))
+}
+
+/// Writes `DD.txt.enc` next to the input of `day`.
+pub fn encrypt_input(day: Day, passphrase: &str) -> Result<PathBuf, InputError> {
+    let plaintext = fs::read(input_path(day))?;
+    let path = encrypted_path(day);
+    fs::write(&path, encrypt(&plaintext, passphrase)?)?;
+    Ok(path)
+}
+
+/// Restores the input of `day` from `DD.txt.enc` and records its checksum.
+pub fn decrypt_input(day: Day, passphrase: &str) -> Result<PathBuf, InputError> {
+    let path = encrypted_path(day);
+    let plaintext = decrypt(&fs::read(&path)?, passphrase, &path)?;
+
+    let input = input_path(day);
+    fs::write(&input, &plaintext)?;
+    record_checksum(day, &plaintext)?;
+    Ok(input)
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::day;
+
+    use super::{checksum, decrypt, encrypt, is_safe_to_commit, Checksums, Verification};
+
+    #[test]
+    fn computes_sha256() {
+        assert_eq!(
+            checksum(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn verifies_checksums() {
+        let mut checksums = Checksums::default();
+        checksums.record(day!(1), b"1\n2\n");
+
+        assert_eq!(checksums.verify(day!(1), b"1\n2\n"), Verification::Valid);
+        assert_eq!(checksums.verify(day!(1), b"1\n3\n"), Verification::Modified);
+        assert_eq!(checksums.verify(day!(2), b""), Verification::Unrecorded);
+    }
+
+    #[test]
+    fn roundtrips_sha256sum_format() {
+        let mut checksums = Checksums::default();
+        checksums.record(day!(2), b"b");
+        checksums.record(day!(1), b"a");
+
+        let s = checksums.to_string();
+        assert!(s.starts_with(&format!("{}  01.txt\n", checksum(b"a"))));
+        assert_eq!(Checksums::parse(&s), checksums);
+        assert_eq!(
+            Checksums::parse(&format!("{} *01.txt", checksum(b"a"))).verify(day!(1), b"a"),
+            Verification::Valid
+        );
+    }
+
+    #[test]
+    fn roundtrips_encryption() {
+        let path = Path::new("01.txt.enc");
+        let encrypted = encrypt(b"puzzle input", "secret").unwrap();
+        assert!(!encrypted
+            .windows(b"puzzle input".len())
+            .any(|w| w == b"puzzle input"));
+        assert_eq!(
+            decrypt(&encrypted, "secret", path).unwrap(),
+            b"puzzle input"
+        );
+        assert!(decrypt(&encrypted, "wrong", path).is_err());
+        assert!(decrypt(b"plain text", "secret", path).is_err());
+    }
+
+    #[test]
+    fn allows_safe_files() {
+        assert!(is_safe_to_commit(Path::new("data/inputs/.keep")));
+        assert!(is_safe_to_commit(Path::new("data/inputs/01.txt.enc")));
+        assert!(is_safe_to_commit(Path::new("data/inputs/SHA256SUMS")));
+        assert!(!is_safe_to_commit(Path::new("data/inputs/01.txt")));
+        assert!(!is_safe_to_commit(Path::new("data/puzzles/01.md")));
+    }
+}
@@ -0,0 +1,411 @@
+/// Reads private leaderboards (`/leaderboard/private/view/ID.json`) and derives per-day statistics:
+/// completion times, the delta between both parts, rank progression and a comparison of all members.
+use std::{collections::HashMap, fmt::Write, str::FromStr};
+
+use tinyjson::JsonValue;
+
+use crate::template::{all_days, Day, SERVER_UTC_OFFSET};
+
+/// Hour (UTC) at which puzzles unlock, midnight on the server.
+const UNLOCK_HOUR_UTC: i64 = -SERVER_UTC_OFFSET as i64;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Member {
+    pub id: u64,
+    /// Anonymous members have no name and are shown as `#ID`.
+    pub name: String,
+    pub local_score: u64,
+    pub stars: u64,
+    /// Unix timestamps at which each part of a day was solved.
+    pub stars_ts: HashMap<Day, [Option<i64>; 2]>,
+}
+
+impl Member {
+    pub fn star(&self, day: Day, part: u8) -> Option<i64> {
+        self.stars_ts.get(&day)?[usize::from(part - 1)]
+    }
+
+    /// Time from the unlock of `day` to solving `part`, in seconds.
+    pub fn solve_time(&self, year: u16, day: Day, part: u8) -> Option<i64> {
+        Some(self.star(day, part)? - unlock_ts(year, day))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    pub year: u16,
+    pub owner_id: u64,
+    /// Sorted by local score, then name.
+    pub members: Vec<Member>,
+}
+
+/// Statistics of one member on one day.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DayStats {
+    pub day: Day,
+    pub part_1: Option<i64>,
+    pub part_2: Option<i64>,
+    /// Rank among members on the leaderboard after this day, by local score.
+    pub rank: usize,
+    pub score: u64,
+}
+
+impl DayStats {
+    /// Seconds between solving part one and part two.
+    pub fn delta(&self) -> Option<i64> {
+        Some(self.part_2? - self.part_1?)
+    }
+}
+
+impl Leaderboard {
+    /// Finds a member by name (case-insensitive) or id.
+    pub fn member(&self, name_or_id: &str) -> Option<&Member> {
+        self.members.iter().find(|member| {
+            member.name.eq_ignore_ascii_case(name_or_id) || member.id.to_string() == name_or_id
+        })
+    }
+
+    pub fn owner(&self) -> Option<&Member> {
+        self.members
+            .iter()
+            .find(|member| member.id == self.owner_id)
+    }
+
+    /// Local scores of every member after each day, in the order of `members`.
+    /// Every star earns as many points as there are members, minus the number of members that were faster.
+    pub fn scores_by_day(&self) -> Vec<Vec<u64>> {
+        let n = self.members.len() as u64;
+        let mut scores = vec![0; self.members.len()];
+        let mut by_day = vec![];
+
+        for day in all_days() {
+            for part in 1..=2 {
+                let mut solved: Vec<(i64, usize)> = self
+                    .members
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, member)| Some((member.star(day, part)?, i)))
+                    .collect();
+                solved.sort_unstable();
+
+                for (position, (_, i)) in solved.into_iter().enumerate() {
+                    scores[i] += n - position as u64;
+                }
+            }
+            by_day.push(scores.clone());
+        }
+
+        by_day
+    }
+
+    /// Completion times and rank progression of `member`, for every day it earned a star on.
+    pub fn day_stats(&self, member: &Member) -> Vec<DayStats> {
+        let index = self
+            .members
+            .iter()
+            .position(|m| m.id == member.id)
+            .expect("member is on the leaderboard");
+
+        all_days()
+            .zip(self.scores_by_day())
+            .filter(|(day, _)| member.stars_ts.contains_key(day))
+            .map(|(day, scores)| DayStats {
+                day,
+                part_1: member.solve_time(self.year, day, 1),
+                part_2: member.solve_time(self.year, day, 2),
+                rank: 1 + scores.iter().filter(|&&s| s > scores[index]).count(),
+                score: scores[index],
+            })
+            .collect()
+    }
+}
+
+/// Unix timestamp of the unlock of `day`, midnight UTC-5.
+pub fn unlock_ts(year: u16, day: Day) -> i64 {
+    days_from_civil(i64::from(year), 12, i64::from(day.into_inner())) * 86_400
+        + UNLOCK_HOUR_UTC * 3600
+}
+
+/// Days since the Unix epoch of a date in the proleptic Gregorian calendar.
+/// See <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Formats a duration like the personal stats page, e.g. `01:02:03`. Durations of over a day are shown as `>24h`.
+pub fn format_time(seconds: Option<i64>) -> String {
+    match seconds {
+        None => "-".into(),
+        Some(s) if s >= 86_400 => ">24h".into(),
+        Some(s) => format!("{:02}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60),
+    }
+}
+
+/// Renders the completion times and rank progression of `member`.
+pub fn day_table(leaderboard: &Leaderboard, member: &Member) -> String {
+    let mut out = String::new();
+    let members = leaderboard.members.len();
+    let mut previous_rank = None;
+
+    writeln!(out, "Day    Part 1    Part 2     Delta  Score  Rank").unwrap();
+    for stats in leaderboard.day_stats(member) {
+        let movement = match previous_rank {
+            Some(previous) if stats.rank < previous => "↑",
+            Some(previous) if stats.rank > previous => "↓",
+            _ => " ",
+        };
+        previous_rank = Some(stats.rank);
+
+        writeln!(
+            out,
+            " {}  {:>8}  {:>8}  {:>8}  {:>5}  {:>2}/{members} {movement}",
+            stats.day,
+            format_time(stats.part_1),
+            format_time(stats.part_2),
+            format_time(stats.delta()),
+            stats.score,
+            stats.rank,
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+/// Renders all members with their stars per day: `*` for both parts, `+` for part one only.
+pub fn member_table(leaderboard: &Leaderboard, highlight: Option<&Member>) -> String {
+    let mut out = String::new();
+    let name_width = leaderboard
+        .members
+        .iter()
+        .map(|member| member.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+
+    // last digit of every day.
+    let days: String = all_days()
+        .map(|day| char::from_digit(u32::from(day.into_inner() % 10), 10).unwrap())
+        .collect();
+    writeln!(out, "  #  {:name_width$}  Score  Stars  {days}", "Member").unwrap();
+
+    for (rank, member) in leaderboard.members.iter().enumerate() {
+        let stars: String = all_days()
+            .map(|day| match member.stars_ts.get(&day) {
+                Some([Some(_), Some(_)]) => '*',
+                Some(_) => '+',
+                None => '.',
+            })
+            .collect();
+        let marker = if highlight.is_some_and(|m| m.id == member.id) {
+            '>'
+        } else {
+            ' '
+        };
+
+        writeln!(
+            out,
+            "{marker}{:>2}  {:name_width$}  {:>5}  {:>5}  {stars}",
+            rank + 1,
+            member.name,
+            member.local_score,
+            member.stars
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl FromStr for Leaderboard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let json = JsonValue::from_str(s).map_err(|e| format!("Invalid leaderboard JSON: {e}"))?;
+        Leaderboard::try_from(&json)
+    }
+}
+
+impl TryFrom<&JsonValue> for Leaderboard {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected leaderboard to be a JSON object.")?;
+
+        let year = json
+            .get("event")
+            .and_then(|v| v.get::<String>())
+            .and_then(|v| v.parse().ok())
+            .ok_or("Expected leaderboard.event to be a year.")?;
+
+        let owner_id = json
+            .get("owner_id")
+            .and_then(|v| v.get::<f64>())
+            .map(|v| *v as u64)
+            .ok_or("Expected leaderboard.owner_id to be a number.")?;
+
+        let mut members = json
+            .get("members")
+            .and_then(|v| v.get::<HashMap<String, JsonValue>>())
+            .ok_or("Expected leaderboard.members to be an object.")?
+            .values()
+            .map(Member::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        members.sort_by(|a, b| b.local_score.cmp(&a.local_score).then(a.name.cmp(&b.name)));
+
+        Ok(Leaderboard {
+            year,
+            owner_id,
+            members,
+        })
+    }
+}
+
+impl TryFrom<&JsonValue> for Member {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected member to be a JSON object.")?;
+
+        let get_number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>())
+                .copied()
+                .ok_or(format!("Expected member.{key} to be a number."))
+        };
+
+        let id = get_number("id")? as u64;
+        let name = json
+            .get("name")
+            .and_then(|v| v.get::<String>())
+            .cloned()
+            .unwrap_or_else(|| format!("#{id}"));
+
+        let mut stars_ts = HashMap::new();
+        let completions = json
+            .get("completion_day_level")
+            .and_then(|v| v.get::<HashMap<String, JsonValue>>())
+            .ok_or("Expected member.completion_day_level to be an object.")?;
+
+        for (day, parts) in completions {
+            let day: Day = day
+                .parse()
+                .map_err(|_| format!("Invalid day `{day}` in member.completion_day_level."))?;
+            let parts = parts
+                .get::<HashMap<String, JsonValue>>()
+                .ok_or("Expected member.completion_day_level to contain objects.")?;
+
+            let star = |part: &str| {
+                parts
+                    .get(part)?
+                    .get::<HashMap<String, JsonValue>>()?
+                    .get("get_star_ts")?
+                    .get::<f64>()
+                    .map(|ts| *ts as i64)
+            };
+            stars_ts.insert(day, [star("1"), star("2")]);
+        }
+
+        Ok(Member {
+            id,
+            name,
+            local_score: get_number("local_score")? as u64,
+            stars: get_number("stars")? as u64,
+            stars_ts,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{format_time, unlock_ts, Leaderboard};
+    use crate::template::Day;
+
+    /// 2024-12-01 05:00 UTC.
+    const DAY_1: i64 = 1_733_029_200;
+
+    fn leaderboard() -> Leaderboard {
+        format!(
+            r#"{{
+                "event": "2024",
+                "owner_id": 1,
+                "members": {{
+                    "1": {{
+                        "id": 1, "name": "alice", "local_score": 11, "stars": 3,
+                        "completion_day_level": {{
+                            "1": {{ "1": {{ "get_star_ts": {}, "star_index": 1 }}, "2": {{ "get_star_ts": {}, "star_index": 3 }} }},
+                            "2": {{ "1": {{ "get_star_ts": {}, "star_index": 5 }} }}
+                        }}
+                    }},
+                    "2": {{
+                        "id": 2, "name": null, "local_score": 10, "stars": 4,
+                        "completion_day_level": {{
+                            "1": {{ "1": {{ "get_star_ts": {}, "star_index": 2 }}, "2": {{ "get_star_ts": {}, "star_index": 4 }} }},
+                            "2": {{ "1": {{ "get_star_ts": {}, "star_index": 6 }}, "2": {{ "get_star_ts": {}, "star_index": 7 }} }}
+                        }}
+                    }}
+                }}
+            }}"#,
+            DAY_1 + 600,
+            DAY_1 + 900,
+            DAY_1 + 86_400 + 4_000,
+            DAY_1 + 700,
+            DAY_1 + 3_700,
+            DAY_1 + 86_400 + 1_000,
+            DAY_1 + 86_400 + 2_000,
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn computes_unlock_times() {
+        assert_eq!(unlock_ts(2024, Day::new(1).unwrap()), DAY_1);
+        assert_eq!(unlock_ts(2024, Day::new(25).unwrap()), DAY_1 + 24 * 86_400);
+    }
+
+    #[test]
+    fn parses_members() {
+        let leaderboard = leaderboard();
+        assert_eq!(leaderboard.year, 2024);
+        assert_eq!(leaderboard.owner().unwrap().name, "alice");
+        assert_eq!(leaderboard.member("#2").unwrap().stars, 4);
+        assert_eq!(leaderboard.member("ALICE").unwrap().id, 1);
+    }
+
+    #[test]
+    fn computes_day_stats() {
+        let leaderboard = leaderboard();
+        let stats = leaderboard.day_stats(leaderboard.member("alice").unwrap());
+
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].part_1, Some(600));
+        assert_eq!(stats[0].delta(), Some(300));
+        assert_eq!((stats[0].score, stats[0].rank), (4, 1));
+        assert_eq!(stats[1].part_2, None);
+        assert_eq!((stats[1].score, stats[1].rank), (5, 2));
+    }
+
+    #[test]
+    fn formats_times() {
+        assert_eq!(format_time(Some(3_723)), "01:02:03");
+        assert_eq!(format_time(Some(90_000)), ">24h");
+        assert_eq!(format_time(None), "-");
+    }
+}
@@ -1,25 +1,53 @@
-use std::{env, fs};
+use std::{env, fs, path::PathBuf};
 
+pub mod alloc_counter;
 pub mod aoc_cli;
+pub mod bitmap;
 pub mod commands;
+pub mod config;
+pub mod differential;
+pub mod report;
+pub mod rng;
 pub mod runner;
+pub mod selection;
 
 pub use day::*;
 
+mod chart;
+#[cfg(feature = "pprof-cpu")]
+mod cpu_profile;
 mod day;
+mod environment;
+mod inputs;
+mod leaderboard;
+mod puzzle;
 mod readme_benchmarks;
 mod run_multi;
+mod throttle;
 mod timings;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
 pub const ANSI_RESET: &str = "\x1b[0m";
 
+/// Resolves a data folder name (`inputs`, `examples`, `puzzles`) to its configured directory.
+/// Other folder names are looked up in `data/`.
+#[must_use]
+pub fn data_dir(folder: &str) -> PathBuf {
+    let config = config::get();
+    let dir = match folder {
+        "inputs" => config.inputs_dir.value.clone(),
+        "examples" => config.examples_dir.value.clone(),
+        "puzzles" => config.puzzles_dir.value.clone(),
+        other => PathBuf::from("data").join(other),
+    };
+    env::current_dir().unwrap().join(dir)
+}
+
 /// Helper function that reads a text file to a string.
 #[must_use]
 pub fn read_file(folder: &str, day: Day) -> String {
-    let cwd = env::current_dir().unwrap();
-    let filepath = cwd.join("data").join(folder).join(format!("{day}.txt"));
+    let filepath = data_dir(folder).join(format!("{day}.txt"));
     let f = fs::read_to_string(filepath);
     f.expect("could not open input file")
 }
@@ -27,11 +55,7 @@ pub fn read_file(folder: &str, day: Day) -> String {
 /// Helper function that reads a text file to string, appending a part suffix. E.g. like `01-2.txt`.
 #[must_use]
 pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
-    let cwd = env::current_dir().unwrap();
-    let filepath = cwd
-        .join("data")
-        .join(folder)
-        .join(format!("{day}-{part}.txt"));
+    let filepath = data_dir(folder).join(format!("{day}-{part}.txt"));
     let f = fs::read_to_string(filepath);
     f.expect("could not open input file")
 }
@@ -39,19 +63,24 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// Alternatively, [`Hooks`](crate::template::runner::Hooks) add modes besides running the parts,
+/// e.g. `solution!(2, explain = explain)` adds `--explain`.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
-        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]);
+        $crate::solution!(@impl $day, {}, [part_one, 1] [part_two, 2]);
     };
     ($day:expr, 1) => {
-        $crate::solution!(@impl $day, [part_one, 1]);
+        $crate::solution!(@impl $day, {}, [part_one, 1]);
     };
     ($day:expr, 2) => {
-        $crate::solution!(@impl $day, [part_two, 2]);
+        $crate::solution!(@impl $day, {}, [part_two, 2]);
+    };
+    ($day:expr, $( $hook:ident = $func:expr ),+ $(,)?) => {
+        $crate::solution!(@impl $day, { $( $hook: Some($func), )+ }, [part_one, 1] [part_two, 2]);
     };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@impl $day:expr, { $( $hook:ident: $func:expr, )* }, $( [$func_part:expr, $part:expr] )*) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
@@ -59,10 +88,30 @@ macro_rules! solution {
         #[global_allocator]
         static ALLOC: dhat::Alloc = dhat::Alloc;
 
+        #[cfg(feature = "count-alloc")]
+        #[global_allocator]
+        static ALLOC: $crate::template::alloc_counter::CountingAlloc =
+            $crate::template::alloc_counter::CountingAlloc;
+
         fn main() {
             use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            let options = <RunOptions as $crate::clap::Parser>::parse();
+            let input = options.read_input(DAY);
+            let hooks = Hooks {
+                $( $hook: $func, )*
+                ..Hooks::default()
+            };
+            if hooks.run(&options, &input, DAY) {
+                return;
+            }
+            if options.time {
+                print_build_profile();
+            }
+            $(
+                if options.runs_part($part) {
+                    run_part($func_part, &input, DAY, $part, &options);
+                }
+            )*
         }
     };
 }
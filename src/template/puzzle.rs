@@ -0,0 +1,356 @@
+/// Renders puzzle descriptions stored by aoc-cli (`data/puzzles/DD.md`) in the terminal.
+/// aoc-cli converts the puzzle page to markdown: `<em>` becomes `*text*`, `<code>` becomes backticks,
+/// and each part starts with a `\--- ... ---` heading underlined by dashes.
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+const PART_TWO_HEADING: &str = "\\--- Part Two ---";
+
+lazy_static! {
+    /// Markdown links, whose text is kept.
+    static ref LINKS: Regex = Regex::new(r"\[([^\]]*)\]\([^)]*\)").unwrap();
+}
+
+/// Returns the description of one part, or `None` if the part is not in the stored puzzle.
+/// Part two is only included once part one is solved.
+pub fn part(markdown: &str, part: u8) -> Option<&str> {
+    match (part, markdown.find(PART_TWO_HEADING)) {
+        (1, Some(index)) => Some(&markdown[..index]),
+        (1, None) => Some(markdown),
+        (2, Some(index)) => Some(&markdown[index..]),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Style {
+    /// Emphasized text, `<em>` on the puzzle page.
+    bold: bool,
+    /// Code, `<code>` on the puzzle page. Emphasized code is how answers to the examples are highlighted.
+    italic: bool,
+}
+
+/// A run of text without whitespace, which may change style in between, e.g. `` `12`, ``.
+type Word = Vec<(String, Style)>;
+
+/// Renders puzzle markdown with ANSI styles, wrapping paragraphs at `width` columns.
+pub fn render(markdown: &str, width: usize) -> String {
+    let mut out = String::new();
+    let mut paragraph: Vec<&str> = vec![];
+    let mut lines = markdown.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            flush_paragraph(&mut out, &mut paragraph, width);
+            end_block(&mut out);
+            for code in lines.by_ref() {
+                if code.trim().starts_with("```") {
+                    break;
+                }
+                out.push_str("    ");
+                push_words(&mut out, &parse_code(code));
+                out.push('\n');
+            }
+            end_block(&mut out);
+        } else if is_heading(trimmed) {
+            flush_paragraph(&mut out, &mut paragraph, width);
+            end_block(&mut out);
+            let heading = trimmed.trim_start_matches('\\');
+            out.push_str(&format!("{ANSI_BOLD}{heading}{ANSI_RESET}\n"));
+            end_block(&mut out);
+            // aoc-cli underlines headings with a line of dashes.
+            if lines
+                .peek()
+                .is_some_and(|next| !next.is_empty() && next.trim().chars().all(|c| c == '-'))
+            {
+                lines.next();
+            }
+        } else if trimmed.is_empty() {
+            flush_paragraph(&mut out, &mut paragraph, width);
+            end_block(&mut out);
+        } else if is_list_item(trimmed) {
+            // list items are not separated by blank lines, but the list is separated from a preceding paragraph.
+            let follows_item = paragraph.first().is_some_and(|line| is_list_item(line));
+            flush_paragraph(&mut out, &mut paragraph, width);
+            if !follows_item {
+                end_block(&mut out);
+            }
+            paragraph.push(trimmed);
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+
+    flush_paragraph(&mut out, &mut paragraph, width);
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// Separates blocks by a blank line.
+fn end_block(out: &mut String) {
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+fn is_heading(line: &str) -> bool {
+    let line = line.trim_start_matches('\\');
+    line.len() > 6 && line.starts_with("---") && line.ends_with("---")
+}
+
+fn is_list_item(line: &str) -> bool {
+    line.starts_with("* ") || line.starts_with("- ")
+}
+
+fn flush_paragraph(out: &mut String, paragraph: &mut Vec<&str>, width: usize) {
+    if paragraph.is_empty() {
+        return;
+    }
+
+    let text = paragraph.join(" ");
+    paragraph.clear();
+
+    if is_list_item(&text) {
+        push_wrapped(out, &parse_inline(&text[2..]), width, "  • ", "    ");
+    } else if let Some(answer) = text.strip_prefix("Your puzzle answer was ") {
+        // highlight the answer like the puzzle page does.
+        let answer = answer.trim_end_matches('.').trim_matches('`');
+        let text = format!("Your puzzle answer was `*{answer}*`.");
+        push_wrapped(out, &parse_inline(&text), width, "", "");
+    } else {
+        push_wrapped(out, &parse_inline(&text), width, "", "");
+    }
+}
+
+/// Splits a paragraph into styled words.
+fn parse_inline(text: &str) -> Vec<Word> {
+    let text = LINKS.replace_all(text, "$1");
+
+    let mut words: Vec<Word> = vec![];
+    let mut word: Word = vec![];
+    let mut style = Style::default();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let rest = &text[index..];
+
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    push_char(&mut word, escaped, style);
+                }
+            }
+            '`' => style.italic = !style.italic,
+            // only treat asterisks as emphasis if they are closed again, e.g. not in `2*4`.
+            '*' if style.bold => style.bold = false,
+            '*' if rest[1..].contains('*')
+                && !rest[1..].starts_with(char::is_whitespace)
+                && !rest[1..].starts_with('*') =>
+            {
+                style.bold = true;
+            }
+            '<' if rest.starts_with("<em>") || rest.starts_with("</em>") => {
+                style.bold = rest.starts_with("<em>");
+                let skip = if style.bold { 3 } else { 4 };
+                chars.nth(skip - 1);
+            }
+            c if c.is_whitespace() => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => push_char(&mut word, c, style),
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Parses a line of a code block, keeping whitespace. Only `<em>` tags are styled,
+/// as asterisks are common in puzzle examples.
+fn parse_code(line: &str) -> Word {
+    let mut word: Word = vec![];
+    let mut bold = false;
+
+    for (i, segment) in line.split("<em>").enumerate() {
+        if i > 0 {
+            bold = true;
+        }
+        for (j, text) in segment.split("</em>").enumerate() {
+            if j > 0 {
+                bold = false;
+            }
+            if !text.is_empty() {
+                word.push((
+                    text.to_string(),
+                    Style {
+                        bold,
+                        italic: false,
+                    },
+                ));
+            }
+        }
+    }
+
+    word
+}
+
+fn push_char(word: &mut Word, c: char, style: Style) {
+    match word.last_mut() {
+        Some((text, last)) if *last == style => text.push(c),
+        _ => word.push((c.to_string(), style)),
+    }
+}
+
+fn word_width(word: &Word) -> usize {
+    word.iter().map(|(text, _)| text.chars().count()).sum()
+}
+
+fn push_words(out: &mut String, word: &Word) {
+    for (text, style) in word {
+        if *style == Style::default() {
+            out.push_str(text);
+        } else {
+            if style.bold {
+                out.push_str(ANSI_BOLD);
+            }
+            if style.italic {
+                out.push_str(ANSI_ITALIC);
+            }
+            out.push_str(text);
+            out.push_str(ANSI_RESET);
+        }
+    }
+}
+
+/// Greedily wraps words at `width` columns. Words longer than a line are not broken.
+fn push_wrapped(out: &mut String, words: &[Word], width: usize, first: &str, indent: &str) {
+    let mut line_width = first.chars().count();
+    out.push_str(first);
+    let mut line_empty = true;
+
+    for word in words {
+        let len = word_width(word);
+        if !line_empty && line_width + 1 + len > width {
+            out.push('\n');
+            out.push_str(indent);
+            line_width = indent.chars().count();
+            line_empty = true;
+        }
+        if !line_empty {
+            out.push(' ');
+            line_width += 1;
+        }
+        push_words(out, word);
+        line_width += len;
+        line_empty = false;
+    }
+
+    out.push('\n');
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{part, render};
+    use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+    const PUZZLE: &str = r"\--- Day 1: Trebuchet?! ---
+----------
+
+Something is wrong with global snow production, and you've been selected to take a look. Each puzzle grants *one star*. Good luck!
+
+For example:
+
+```
+1abc2
+pqr3stu8vwx
+```
+
+In this example, adding these together produces `*142*`. Operators like `2*4` stay as they are, see [the FAQ](/about).
+
+* Item one
+* Item two
+
+Your puzzle answer was `54331`.
+
+\--- Part Two ---
+----------
+
+Some of the digits are actually *spelled out with letters*.
+
+Your puzzle answer was `54518`.
+";
+
+    fn strip_ansi(text: &str) -> String {
+        text.replace(ANSI_BOLD, "")
+            .replace(ANSI_ITALIC, "")
+            .replace(ANSI_RESET, "")
+    }
+
+    #[test]
+    fn splits_parts() {
+        assert!(part(PUZZLE, 1).unwrap().ends_with("`54331`.\n\n"));
+        assert!(part(PUZZLE, 2).unwrap().starts_with("\\--- Part Two ---"));
+        assert_eq!(part("\\--- Day 1: Test ---\n", 2), None);
+    }
+
+    #[test]
+    fn renders_blocks() {
+        let rendered = strip_ansi(&render(PUZZLE, 40));
+        let expected = "--- Day 1: Trebuchet?! ---
+
+Something is wrong with global snow
+production, and you've been selected to
+take a look. Each puzzle grants one
+star. Good luck!
+
+For example:
+
+    1abc2
+    pqr3stu8vwx
+
+In this example, adding these together
+produces 142. Operators like 2*4 stay as
+they are, see the FAQ.
+
+  • Item one
+  • Item two
+
+Your puzzle answer was 54331.
+
+--- Part Two ---
+
+Some of the digits are actually spelled
+out with letters.
+
+Your puzzle answer was 54518.
+";
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn styles_emphasis_and_answers() {
+        let rendered = render(PUZZLE, 80);
+        assert!(rendered.contains(&format!("{ANSI_BOLD}one{ANSI_RESET}")));
+        assert!(rendered.contains(&format!("{ANSI_BOLD}{ANSI_ITALIC}142{ANSI_RESET}")));
+        assert!(rendered.contains(&format!("{ANSI_ITALIC}2*4{ANSI_RESET}")));
+        assert!(rendered.contains(&format!("{ANSI_BOLD}{ANSI_ITALIC}54331{ANSI_RESET}")));
+    }
+
+    #[test]
+    fn highlights_em_tags_in_code_blocks() {
+        let rendered = render("```\n1 <em>2</em> 3\n```\n", 80);
+        assert_eq!(rendered, format!("    1 {ANSI_BOLD}2{ANSI_RESET} 3\n"));
+    }
+}
@@ -1,10 +1,11 @@
 /// Module that updates the readme me with timing information.
 /// The approach taken is similar to how `aoc-readme-stars` handles this.
-use std::{fs, io};
+use std::{fs, io, time::Duration};
 
-use crate::template::timings::Timings;
-use crate::template::Day;
+use crate::template::timings::{format_bytes, MemoryStats, Timings};
+use crate::template::{config, Day};
 
+#[cfg(test)]
 static MARKER: &str = "<!--- benchmarking table --->";
 
 #[allow(dead_code)]
@@ -21,17 +22,17 @@ impl From<std::io::Error> for Error {
 }
 
 pub struct TablePosition {
-    pos_start: usize,
-    pos_end: usize,
+    pub pos_start: usize,
+    pub pos_end: usize,
 }
 
 #[must_use]
 pub fn get_path_for_bin(day: Day) -> String {
-    format!("./src/bin/{day}.rs")
+    format!("./{}/{day}.rs", config::get().bin_dir.value.display())
 }
 
-fn locate_table(readme: &str) -> Result<TablePosition, Error> {
-    let matches: Vec<_> = readme.match_indices(MARKER).collect();
+pub fn locate_table(readme: &str, marker: &str) -> Result<TablePosition, Error> {
+    let matches: Vec<_> = readme.match_indices(marker).collect();
 
     if matches.len() > 2 {
         return Err(Error::Parser(
@@ -52,78 +53,196 @@ fn locate_table(readme: &str) -> Result<TablePosition, Error> {
     Ok(TablePosition { pos_start, pos_end })
 }
 
-fn construct_table(prefix: &str, timings: Timings, total_millis: f64) -> String {
+fn format_memory(memory: Option<MemoryStats>) -> String {
+    memory.map_or_else(
+        || "-".into(),
+        |m| {
+            format!(
+                "{} / {} / {}",
+                format_bytes(m.peak_bytes),
+                format_bytes(m.total_bytes),
+                m.allocations
+            )
+        },
+    )
+}
+
+/// Sums up the benched durations of a part over all days, formatted like a single timing.
+fn format_part_total(timings: &Timings, part: u8) -> String {
+    let nanos: Vec<u64> = timings
+        .data
+        .iter()
+        .filter_map(|t| t.part_nanos(part))
+        .collect();
+
+    if nanos.is_empty() {
+        return "-".into();
+    }
+
+    let total = Duration::from_nanos(nanos.iter().sum());
+    format!("{total:.1?}")
+}
+
+/// Builds the benchmark table, without the surrounding markers.
+/// With `chart`, links the chart image written by `cargo time --chart` below the table.
+pub fn construct_table(
+    prefix: &str,
+    timings: Timings,
+    total_millis: f64,
+    memory_columns: bool,
+    chart: Option<&str>,
+) -> String {
     let header = format!("{prefix} Benchmarks");
 
-    let mut lines: Vec<String> = vec![
-        MARKER.into(),
-        header,
-        String::new(),
-        "| Day | Part 1 | Part 2 |".into(),
-        "| :---: | :---: | :---:  |".into(),
+    let mut lines: Vec<String> = vec![header, String::new()];
+    let part_totals = [
+        format_part_total(&timings, 1),
+        format_part_total(&timings, 2),
     ];
 
+    if memory_columns {
+        lines.push("| Day | Part 1 | Part 2 | Part 1 Heap | Part 2 Heap |".into());
+        lines.push("| :---: | :---: | :---:  | :---: | :---: |".into());
+    } else {
+        lines.push("| Day | Part 1 | Part 2 |".into());
+        lines.push("| :---: | :---: | :---:  |".into());
+    }
+
     for timing in timings.data {
         let path = get_path_for_bin(timing.day);
-        lines.push(format!(
+        let mut line = format!(
             "| [Day {}]({}) | `{}` | `{}` |",
             timing.day.into_inner(),
             path,
-            timing.part_1.unwrap_or_else(|| "-".into()),
-            timing.part_2.unwrap_or_else(|| "-".into())
-        ));
+            timing.part_1.map_or_else(|| "-".into(), |p| p.to_string()),
+            timing.part_2.map_or_else(|| "-".into(), |p| p.to_string())
+        );
+        if memory_columns {
+            line.push_str(&format!(
+                " `{}` | `{}` |",
+                format_memory(timing.part_1_memory),
+                format_memory(timing.part_2_memory)
+            ));
+        }
+        lines.push(line);
     }
 
+    let mut total_line = format!(
+        "| **Total** | `{}` | `{}` |",
+        part_totals[0], part_totals[1]
+    );
+    if memory_columns {
+        total_line.push_str(" | |");
+    }
+    lines.push(total_line);
+
     lines.push(String::new());
     lines.push(format!("**Total: {total_millis:.2}ms**"));
-    lines.push(MARKER.into());
+    if memory_columns {
+        lines.push(String::new());
+        lines.push("_Heap: peak / total allocated / allocations, recorded with dhat._".into());
+    }
+    if let Some(chart) = chart {
+        lines.push(String::new());
+        lines.push(format!("![Benchmark chart]({chart})"));
+    }
 
     lines.join("\n")
 }
 
-fn update_content(s: &mut String, timings: Timings, total_millis: f64) -> Result<(), Error> {
-    let positions = locate_table(s)?;
-    let table = construct_table("##", timings, total_millis);
-    s.replace_range(positions.pos_start..positions.pos_end, &table);
+fn update_content(
+    s: &mut String,
+    marker: &str,
+    timings: Timings,
+    total_millis: f64,
+    memory_columns: bool,
+    chart: Option<&str>,
+) -> Result<(), Error> {
+    let positions = locate_table(s, marker)?;
+    let table = construct_table("##", timings, total_millis, memory_columns, chart);
+    s.replace_range(
+        positions.pos_start..positions.pos_end,
+        &format!("{marker}\n{table}\n{marker}"),
+    );
     Ok(())
 }
 
+/// Returns the README link to the benchmark chart, if `readme.chart` is enabled.
+pub fn chart_link() -> Option<String> {
+    let config = config::get();
+    config
+        .readme_chart
+        .value
+        .then(|| format!("./{}", config.chart_file.value.display()))
+}
+
 pub fn update(timings: Timings) -> Result<(), Error> {
-    let path = "README.md";
+    let config = config::get();
+    let path = &config.readme_file.value;
     let mut readme = String::from_utf8_lossy(&fs::read(path)?).to_string();
     let total_millis = timings.total_millis();
-    update_content(&mut readme, timings, total_millis)?;
+    update_content(
+        &mut readme,
+        &config.readme_benchmark_marker.value,
+        timings,
+        total_millis,
+        config.readme_memory_columns.value,
+        chart_link().as_deref(),
+    )?;
     fs::write(path, &readme)?;
     Ok(())
 }
 
-#[cfg(feature = "test_lib")]
+#[cfg(test)]
 mod tests {
     use super::{update_content, MARKER};
-    use crate::{day, template::timings::Timing, template::timings::Timings};
+    use crate::{
+        day,
+        template::timings::{MemoryStats, PartTiming, Timing, Timings},
+    };
+
+    fn millis(millis: u64) -> Option<PartTiming> {
+        Some(PartTiming {
+            nanos: millis * 1_000_000,
+            samples: Some(10),
+        })
+    }
 
     fn get_mock_timings() -> Timings {
         Timings {
             data: vec![
                 Timing {
                     day: day!(1),
-                    part_1: Some("10ms".into()),
-                    part_2: Some("20ms".into()),
-                    total_nanos: 3e+10,
+                    part_1: millis(10),
+                    part_2: millis(20),
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 },
                 Timing {
                     day: day!(2),
-                    part_1: Some("30ms".into()),
-                    part_2: Some("40ms".into()),
-                    total_nanos: 7e+10,
+                    part_1: millis(30),
+                    part_2: millis(40),
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 },
                 Timing {
                     day: day!(4),
-                    part_1: Some("40ms".into()),
-                    part_2: Some("50ms".into()),
-                    total_nanos: 9e+10,
+                    part_1: millis(40),
+                    part_2: millis(50),
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 },
             ],
+            environment: None,
         }
     }
 
@@ -131,28 +250,28 @@ mod tests {
     #[should_panic]
     fn errors_if_marker_not_present() {
         let mut s = "# readme".to_string();
-        update_content(&mut s, get_mock_timings(), 190.0).unwrap();
+        update_content(&mut s, MARKER, get_mock_timings(), 190.0, false, None).unwrap();
     }
 
     #[test]
     #[should_panic]
     fn errors_if_too_many_markers_present() {
         let mut s = format!("{} {} {}", MARKER, MARKER, MARKER);
-        update_content(&mut s, get_mock_timings(), 190.0).unwrap();
+        update_content(&mut s, MARKER, get_mock_timings(), 190.0, false, None).unwrap();
     }
 
     #[test]
     fn updates_empty_benchmarks() {
         let mut s = format!("foo\nbar\n{}{}\nbaz", MARKER, MARKER);
-        update_content(&mut s, get_mock_timings(), 190.0).unwrap();
-        assert_eq!(s.contains("## Benchmarks"), true);
+        update_content(&mut s, MARKER, get_mock_timings(), 190.0, false, None).unwrap();
+        assert!(s.contains("## Benchmarks"));
     }
 
     #[test]
     fn updates_existing_benchmarks() {
         let mut s = format!("foo\nbar\n{}{}\nbaz", MARKER, MARKER);
-        update_content(&mut s, get_mock_timings(), 190.0).unwrap();
-        update_content(&mut s, get_mock_timings(), 190.0).unwrap();
+        update_content(&mut s, MARKER, get_mock_timings(), 190.0, false, None).unwrap();
+        update_content(&mut s, MARKER, get_mock_timings(), 190.0, false, None).unwrap();
         assert_eq!(s.matches(MARKER).collect::<Vec<&str>>().len(), 2);
         assert_eq!(s.matches("## Benchmarks").collect::<Vec<&str>>().len(), 1);
     }
@@ -160,7 +279,7 @@ mod tests {
     #[test]
     fn format_benchmarks() {
         let mut s = format!("foo\nbar\n{}\n{}\nbaz", MARKER, MARKER);
-        update_content(&mut s, get_mock_timings(), 190.0).unwrap();
+        update_content(&mut s, MARKER, get_mock_timings(), 190.0, false, None).unwrap();
         let expected = [
             "foo",
             "bar",
@@ -169,9 +288,10 @@ mod tests {
             "",
             "| Day | Part 1 | Part 2 |",
             "| :---: | :---: | :---:  |",
-            "| [Day 1](./src/bin/01.rs) | `10ms` | `20ms` |",
-            "| [Day 2](./src/bin/02.rs) | `30ms` | `40ms` |",
-            "| [Day 4](./src/bin/04.rs) | `40ms` | `50ms` |",
+            "| [Day 1](./src/bin/01.rs) | `10.0ms` | `20.0ms` |",
+            "| [Day 2](./src/bin/02.rs) | `30.0ms` | `40.0ms` |",
+            "| [Day 4](./src/bin/04.rs) | `40.0ms` | `50.0ms` |",
+            "| **Total** | `80.0ms` | `110.0ms` |",
             "",
             "**Total: 190.00ms**",
             "<!--- benchmarking table --->",
@@ -180,4 +300,38 @@ mod tests {
         .join("\n");
         assert_eq!(s, expected);
     }
+
+    #[test]
+    fn format_benchmarks_with_memory() {
+        let mut timings = get_mock_timings();
+        timings.data[0].part_1_memory = Some(MemoryStats {
+            peak_bytes: 1536,
+            total_bytes: 3 * 1024 * 1024,
+            allocations: 42,
+        });
+
+        let mut s = format!("{}\n{}", MARKER, MARKER);
+        update_content(&mut s, MARKER, timings, 190.0, true, None).unwrap();
+
+        assert!(s.contains("| Day | Part 1 | Part 2 | Part 1 Heap | Part 2 Heap |"));
+        assert!(s.contains(
+            "| [Day 1](./src/bin/01.rs) | `10.0ms` | `20.0ms` | `1.5 KiB / 3.0 MiB / 42` | `-` |"
+        ));
+    }
+
+    #[test]
+    fn format_benchmarks_with_chart() {
+        let mut s = format!("{}\n{}", MARKER, MARKER);
+        update_content(
+            &mut s,
+            MARKER,
+            get_mock_timings(),
+            190.0,
+            false,
+            Some("./data/timings.svg"),
+        )
+        .unwrap();
+
+        assert!(s.contains("**Total: 190.00ms**\n\n![Benchmark chart](./data/timings.svg)\n"));
+    }
 }
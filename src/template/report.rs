@@ -0,0 +1,517 @@
+/// Generates the sections of the README (stars, benchmarks, links and notes) and standalone reports.
+/// Every section lives between a pair of markers, so the README can be regenerated without touching other content.
+use std::{fs, path::Path};
+
+use clap::ValueEnum;
+
+use crate::template::readme_benchmarks::{
+    chart_link, construct_table, get_path_for_bin, locate_table, Error,
+};
+use crate::template::timings::Timings;
+use crate::template::{all_days, config, data_dir, Day};
+
+/// Line that aoc-cli keeps in a downloaded puzzle description for every solved part.
+const ANSWER_PREFIX: &str = "Your puzzle answer was";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    #[value(name = "md")]
+    Markdown,
+    Html,
+}
+
+impl Format {
+    /// Guesses the format from a file extension, defaulting to Markdown.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("html" | "htm") => Format::Html,
+            _ => Format::Markdown,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    Stars,
+    Benchmarks,
+    Links,
+    Notes,
+}
+
+impl Section {
+    pub const ALL: [Section; 4] = [
+        Section::Stars,
+        Section::Benchmarks,
+        Section::Links,
+        Section::Notes,
+    ];
+
+    pub fn marker(self) -> String {
+        let config = config::get();
+        match self {
+            Section::Stars => config.readme_stars_marker.value.clone(),
+            Section::Benchmarks => config.readme_benchmark_marker.value.clone(),
+            Section::Links => config.readme_links_marker.value.clone(),
+            Section::Notes => config.readme_notes_marker.value.clone(),
+        }
+    }
+}
+
+/// What is known about a single day.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DayReport {
+    pub day: Day,
+    /// Path of the solution, if the day was scaffolded.
+    pub solution: Option<String>,
+    pub stars: u8,
+    /// The inner doc comment (`//!`) at the top of the solution.
+    pub notes: Option<String>,
+}
+
+/// Everything a report is built from.
+#[derive(Clone, Debug, Default)]
+pub struct ReportData {
+    pub year: Option<u16>,
+    pub days: Vec<DayReport>,
+    pub timings: Timings,
+}
+
+impl ReportData {
+    /// Collects stars from the downloaded puzzle descriptions, notes from the solutions and the stored timings.
+    pub fn collect() -> Self {
+        let days = all_days()
+            .filter_map(|day| {
+                let path = get_path_for_bin(day);
+                let source = fs::read_to_string(&path).ok();
+                let puzzle = fs::read_to_string(data_dir("puzzles").join(format!("{day}.md")));
+                let stars = puzzle.map_or(0, |p| count_stars(&p));
+
+                if source.is_none() && stars == 0 {
+                    return None;
+                }
+
+                Some(DayReport {
+                    day,
+                    notes: source.as_deref().and_then(parse_notes),
+                    solution: source.map(|_| path),
+                    stars,
+                })
+            })
+            .collect();
+
+        ReportData {
+            year: config::get().year.value,
+            days,
+            timings: Timings::read_from_file(),
+        }
+    }
+
+    fn puzzle_url(&self, day: Day) -> Option<String> {
+        self.year
+            .map(|year| format!("https://adventofcode.com/{year}/day/{}", day.into_inner()))
+    }
+
+    /// Renders a section as Markdown, without markers.
+    pub fn section(&self, section: Section) -> String {
+        match section {
+            Section::Stars => self.stars(),
+            Section::Benchmarks => {
+                let total_millis = self.timings.total_millis();
+                construct_table(
+                    "##",
+                    self.timings.clone(),
+                    total_millis,
+                    config::get().readme_memory_columns.value,
+                    chart_link().as_deref(),
+                )
+            }
+            Section::Links => self.links(),
+            Section::Notes => self.notes(),
+        }
+    }
+
+    fn day_label(&self, day: Day) -> String {
+        let label = format!("Day {}", day.into_inner());
+        match self.puzzle_url(day) {
+            Some(url) => format!("[{label}]({url})"),
+            None => label,
+        }
+    }
+
+    fn stars(&self) -> String {
+        let header = match self.year {
+            Some(year) => format!("## {year} Results"),
+            None => "## Results".into(),
+        };
+
+        let mut lines = vec![
+            header,
+            String::new(),
+            "| Day | Part 1 | Part 2 |".into(),
+            "| :---: | :---: | :---: |".into(),
+        ];
+
+        for day in &self.days {
+            let star = |part: u8| if day.stars >= part { "⭐" } else { " " };
+            lines.push(format!(
+                "| {} | {} | {} |",
+                self.day_label(day.day),
+                star(1),
+                star(2)
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    fn links(&self) -> String {
+        let mut lines = vec![
+            "## Links".into(),
+            String::new(),
+            "| Day | Puzzle | Solution |".into(),
+            "| :---: | :---: | :---: |".into(),
+        ];
+
+        for day in &self.days {
+            let puzzle = self
+                .puzzle_url(day.day)
+                .map_or_else(|| "-".into(), |url| format!("[adventofcode.com]({url})"));
+            let solution = day.solution.as_ref().map_or_else(
+                || "-".into(),
+                |path| format!("[{}]({path})", path.trim_start_matches("./")),
+            );
+            lines.push(format!(
+                "| Day {} | {puzzle} | {solution} |",
+                day.day.into_inner()
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    fn notes(&self) -> String {
+        let mut lines = vec!["## Notes".into()];
+
+        for day in &self.days {
+            let Some(notes) = &day.notes else {
+                continue;
+            };
+
+            lines.push(String::new());
+            lines.push(format!("### {}", self.day_label(day.day)));
+            lines.push(String::new());
+            lines.push(notes.clone());
+        }
+
+        lines.join("\n")
+    }
+
+    /// Renders all sections as a standalone document.
+    pub fn document(&self, format: Format) -> String {
+        let title = match self.year {
+            Some(year) => format!("Advent of Code {year}"),
+            None => "Advent of Code".into(),
+        };
+
+        let mut markdown = vec![format!("# {title}")];
+        for section in Section::ALL {
+            markdown.push(String::new());
+            markdown.push(self.section(section));
+        }
+        let markdown = markdown.join("\n") + "\n";
+
+        match format {
+            Format::Markdown => markdown,
+            Format::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+                <style>body {{ font-family: sans-serif; max-width: 60rem; margin: auto; }} \
+                table {{ border-collapse: collapse; }} td, th {{ padding: 0.25rem 0.75rem; text-align: center; }}</style>\n\
+                </head>\n<body>\n{}</body>\n</html>\n",
+                escape_html(&title),
+                markdown_to_html(&markdown)
+            ),
+        }
+    }
+
+    /// Replaces every section whose markers are present in `readme`. Returns the updated sections.
+    pub fn update_readme(&self, readme: &mut String) -> Result<Vec<Section>, Error> {
+        let mut updated = vec![];
+
+        for section in Section::ALL {
+            let marker = section.marker();
+            if !readme.contains(&marker) {
+                continue;
+            }
+
+            let position = locate_table(readme, &marker)?;
+            let content = format!("{marker}\n{}\n{marker}", self.section(section));
+            readme.replace_range(position.pos_start..position.pos_end, &content);
+            updated.push(section);
+        }
+
+        Ok(updated)
+    }
+}
+
+/// Counts the parts of a puzzle description that have an answer.
+fn count_stars(puzzle: &str) -> u8 {
+    #[allow(clippy::cast_possible_truncation)]
+    let stars = puzzle.matches(ANSWER_PREFIX).count().min(2) as u8;
+    stars
+}
+
+/// Extracts the inner doc comment (`//!`) at the top of a solution.
+fn parse_notes(source: &str) -> Option<String> {
+    let lines: Vec<&str> = source
+        .lines()
+        .map(str::trim_start)
+        .take_while(|line| line.starts_with("//!"))
+        .map(|line| {
+            let line = line.trim_start_matches("//!");
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect();
+
+    let notes = lines.join("\n").trim().to_string();
+    (!notes.is_empty()).then_some(notes)
+}
+
+/* -------------------------------------------------------------------------- */
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Converts the subset of Markdown used by the report (headings, tables, paragraphs, code, links and emphasis) to HTML.
+fn markdown_to_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = vec![];
+    let mut table: Vec<Vec<&str>> = vec![];
+
+    let flush = |html: &mut String, paragraph: &mut Vec<&str>, table: &mut Vec<Vec<&str>>| {
+        if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", inline_html(&paragraph.join("\n"))));
+            paragraph.clear();
+        }
+        if !table.is_empty() {
+            html.push_str("<table>\n");
+            for (i, row) in table.iter().enumerate() {
+                let tag = if i == 0 { "th" } else { "td" };
+                let cells: String = row
+                    .iter()
+                    .map(|cell| format!("<{tag}>{}</{tag}>", inline_html(cell)))
+                    .collect();
+                html.push_str(&format!("<tr>{cells}</tr>\n"));
+            }
+            html.push_str("</table>\n");
+            table.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with("<!--") {
+            flush(&mut html, &mut paragraph, &mut table);
+        } else if let Some(row) = trimmed.strip_prefix('|') {
+            if !paragraph.is_empty() {
+                flush(&mut html, &mut paragraph, &mut table);
+            }
+            let cells: Vec<&str> = row
+                .trim_end_matches('|')
+                .split('|')
+                .map(str::trim)
+                .collect();
+            let is_separator = cells
+                .iter()
+                .all(|c| !c.is_empty() && c.chars().all(|c| c == ':' || c == '-'));
+            if !is_separator {
+                table.push(cells);
+            }
+        } else if trimmed.starts_with('#') {
+            flush(&mut html, &mut paragraph, &mut table);
+            let level = trimmed.chars().take_while(|c| *c == '#').count().min(6);
+            let text = trimmed[level..].trim();
+            html.push_str(&format!("<h{level}>{}</h{level}>\n", inline_html(text)));
+        } else {
+            if !table.is_empty() {
+                flush(&mut html, &mut paragraph, &mut table);
+            }
+            paragraph.push(trimmed);
+        }
+    }
+
+    flush(&mut html, &mut paragraph, &mut table);
+    html
+}
+
+fn inline_html(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+
+        if c == '`' {
+            if let Some((code, tail)) = after.split_once('`') {
+                html.push_str(&format!("<code>{}</code>", escape_html(code)));
+                rest = tail;
+                continue;
+            }
+        }
+
+        if c == '[' {
+            if let Some((label, tail)) = after.split_once("](") {
+                if let Some((href, tail)) = tail.split_once(')') {
+                    html.push_str(&format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(href),
+                        inline_html(label)
+                    ));
+                    rest = tail;
+                    continue;
+                }
+            }
+        }
+
+        if let Some(after) = rest.strip_prefix("**") {
+            if let Some((strong, tail)) = after.split_once("**") {
+                html.push_str(&format!("<strong>{}</strong>", inline_html(strong)));
+                rest = tail;
+                continue;
+            }
+        }
+
+        if c == '_' && html.chars().last().is_none_or(char::is_whitespace) {
+            if let Some((em, tail)) = after.split_once('_') {
+                if tail.chars().next().is_none_or(|c| !c.is_alphanumeric()) {
+                    html.push_str(&format!("<em>{}</em>", inline_html(em)));
+                    rest = tail;
+                    continue;
+                }
+            }
+        }
+
+        html.push_str(&escape_html(&c.to_string()));
+        rest = after;
+    }
+
+    html
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::{count_stars, markdown_to_html, parse_notes, DayReport, ReportData, Section};
+    use crate::day;
+
+    fn get_mock_data() -> ReportData {
+        ReportData {
+            year: Some(2024),
+            days: vec![
+                DayReport {
+                    day: day!(1),
+                    solution: Some("./src/bin/01.rs".into()),
+                    stars: 2,
+                    notes: Some("Sorts both lists.".into()),
+                },
+                DayReport {
+                    day: day!(2),
+                    solution: None,
+                    stars: 1,
+                    notes: None,
+                },
+            ],
+            ..ReportData::default()
+        }
+    }
+
+    #[test]
+    fn counts_stars() {
+        assert_eq!(count_stars("--- Day 1 ---"), 0);
+        assert_eq!(
+            count_stars("Your puzzle answer was `11`.\n--- Part Two ---"),
+            1
+        );
+        assert_eq!(
+            count_stars("Your puzzle answer was `11`.\nYour puzzle answer was `31`."),
+            2
+        );
+    }
+
+    #[test]
+    fn parses_notes() {
+        let source =
+            "//! Sorts both lists.\n//!\n//! Part two counts.\nuse std::fs;\n//! not a note";
+        assert_eq!(
+            parse_notes(source),
+            Some("Sorts both lists.\n\nPart two counts.".into())
+        );
+        assert_eq!(parse_notes("use std::fs;"), None);
+    }
+
+    #[test]
+    fn formats_stars() {
+        let stars = get_mock_data().section(Section::Stars);
+        assert!(stars.starts_with("## 2024 Results"));
+        assert!(stars.contains("| [Day 1](https://adventofcode.com/2024/day/1) | ⭐ | ⭐ |"));
+        assert!(stars.contains("| [Day 2](https://adventofcode.com/2024/day/2) | ⭐ |   |"));
+    }
+
+    #[test]
+    fn formats_links() {
+        let links = get_mock_data().section(Section::Links);
+        assert!(links.contains(
+            "| Day 1 | [adventofcode.com](https://adventofcode.com/2024/day/1) | [src/bin/01.rs](./src/bin/01.rs) |"
+        ));
+        assert!(links
+            .contains("| Day 2 | [adventofcode.com](https://adventofcode.com/2024/day/2) | - |"));
+    }
+
+    #[test]
+    fn formats_notes() {
+        let notes = get_mock_data().section(Section::Notes);
+        assert_eq!(
+            notes,
+            "## Notes\n\n### [Day 1](https://adventofcode.com/2024/day/1)\n\nSorts both lists."
+        );
+    }
+
+    #[test]
+    fn updates_marked_sections_only() {
+        let notes_marker = Section::Notes.marker();
+        let mut readme = format!("# readme\n{notes_marker}\nold\n{notes_marker}\nfooter");
+
+        let updated = get_mock_data().update_readme(&mut readme).unwrap();
+
+        assert_eq!(updated, vec![Section::Notes]);
+        assert!(readme.starts_with("# readme\n"));
+        assert!(readme.ends_with("\nfooter"));
+        assert!(readme.contains("Sorts both lists."));
+        assert!(!readme.contains("old"));
+    }
+
+    #[test]
+    fn converts_markdown_to_html() {
+        let html = markdown_to_html(
+            "## Bench & co\n\n| Day | Part 1 |\n| :---: | :---: |\n| [Day 1](./a.rs) | `1ms` |\n\n**Total: 1ms**\n_Heap: peak_",
+        );
+        assert_eq!(
+            html,
+            [
+                "<h2>Bench &amp; co</h2>",
+                "<table>",
+                "<tr><th>Day</th><th>Part 1</th></tr>",
+                "<tr><td><a href=\"./a.rs\">Day 1</a></td><td><code>1ms</code></td></tr>",
+                "</table>",
+                "<p><strong>Total: 1ms</strong>\n<em>Heap: peak</em></p>",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -1,41 +1,96 @@
-use std::{collections::HashSet, io};
+use std::io;
 
-use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{commands::solve::Profiler, config, Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
 
 use super::{
-    all_days,
+    environment::{self, Environment},
+    selection::{source_hash, Selection},
     timings::{Timing, Timings},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
-    let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
+/// Run the selected solutions. Every solution is run once more per passed profiler,
+/// e.g. in the `dhat` profile to record its heap usage.
+pub fn run_multi(
+    selection: &Selection,
+    is_release: bool,
+    is_timed: bool,
+    profilers: &[Profiler],
+) -> Option<Timings> {
+    let mut timings: Vec<Timing> = vec![];
+    let mut profiles: Vec<String> = vec![];
 
     let mut need_space = false;
+    let count_instructions =
+        profilers.contains(&Profiler::Counts) && child_commands::has_valgrind();
 
-    // NOTE: use non-duplicate, sorted day values.
-    all_days()
-        .filter(|day| days_to_run.contains(day))
-        .for_each(|day| {
-            if need_space {
-                println!();
-            }
-            need_space = true;
+    // NOTE: `Days` are non-duplicate and sorted.
+    selection.days.iter().for_each(|day| {
+        if need_space {
+            println!();
+        }
+        need_space = true;
 
-            println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
-            println!("------");
+        println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
+        println!("------");
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+        let output =
+            child_commands::run_solution(day, selection.part, is_timed, is_release, None).unwrap();
 
-            if output.is_empty() {
-                println!("Not solved.");
-            } else {
-                let val = child_commands::parse_exec_time(&output, day);
-                timings.push(val);
+        if output.is_empty() {
+            println!("Not solved.");
+        } else {
+            let mut val = child_commands::parse_exec_time(&output, day);
+            if let Some(profile) = child_commands::parse_profile(&output) {
+                if !profiles.contains(&profile) {
+                    profiles.push(profile);
+                }
             }
-        });
+            val.source_hash = source_hash(day);
+
+            for &profiler in profilers {
+                let label = match profiler {
+                    Profiler::Heap => "Profiling heap...",
+                    Profiler::Cpu => "Profiling CPU...",
+                    Profiler::Counts => "Counting allocations...",
+                };
+                println!("{ANSI_ITALIC}{label}{ANSI_RESET}");
+
+                let output =
+                    child_commands::run_solution(day, selection.part, false, false, Some(profiler))
+                        .unwrap();
+
+                match profiler {
+                    Profiler::Heap => child_commands::parse_memory(&output, &mut val),
+                    Profiler::Cpu => {}
+                    Profiler::Counts => {
+                        child_commands::parse_counts(&output, &mut val);
+                        if count_instructions {
+                            println!("{ANSI_ITALIC}Counting instructions...{ANSI_RESET}");
+                            child_commands::count_instructions(day, &mut val);
+                        }
+                    }
+                }
+            }
+
+            timings.push(val);
+        }
+    });
+
+    if profilers.contains(&Profiler::Counts) && !count_instructions {
+        eprintln!("\nvalgrind not found, instruction counts were skipped.");
+    }
 
     if is_timed {
-        let timings = Timings { data: timings };
+        // the profile reported by the solutions, which is the expected one if none ran.
+        let profile = if profiles.is_empty() {
+            environment::cargo_profile(is_release).to_string()
+        } else {
+            profiles.join(", ")
+        };
+        let timings = Timings {
+            data: timings,
+            environment: Some(Environment::capture(&profile)),
+        };
         let total_millis = timings.total_millis();
         println!(
             "\n{ANSI_BOLD}Total (Run):{ANSI_RESET} {ANSI_ITALIC}{total_millis:.2}ms{ANSI_RESET}"
@@ -61,41 +116,62 @@ impl From<std::io::Error> for Error {
 
 #[must_use]
 pub fn get_path_for_bin(day: Day) -> String {
-    format!("./src/bin/{day}.rs")
+    format!("./{}/{day}.rs", config::get().bin_dir.value.display())
 }
 
 /// All solutions live in isolated binaries.
 /// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
 pub mod child_commands {
     use super::{get_path_for_bin, Error};
-    use crate::template::Day;
+    use crate::template::{
+        commands::solve::Profiler,
+        runner::PROFILE_LABEL,
+        timings::{parse_duration, Counts, MemoryStats, PartTiming},
+        Day, ANSI_RESET,
+    };
     use std::{
+        env,
         io::{BufRead, BufReader},
-        path::Path,
+        path::{Path, PathBuf},
         process::{Command, Stdio},
         thread,
     };
 
     /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    pub fn run_solution(
+        day: Day,
+        part: Option<u8>,
+        is_timed: bool,
+        is_release: bool,
+        profiler: Option<Profiler>,
+    ) -> Result<Vec<String>, Error> {
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
             return Ok(vec![]);
         }
 
         let day_padded = day.to_string();
+        let part_str = part.map(|p| p.to_string());
         let mut args = vec!["run", "--quiet", "--bin", &day_padded];
 
-        if is_release {
+        if let Some(profiler) = profiler {
+            args.extend(profiler.cargo_args());
+        } else if is_release {
             args.push("--release");
         }
 
+        args.push("--");
+
         if is_timed {
             // mirror `--time` flag to child invocations.
-            args.push("--");
             args.push("--time");
         }
 
+        if let Some(part) = &part_str {
+            args.push("--part");
+            args.push(part);
+        }
+
         // spawn child command with piped stdout/stderr.
         // forward output to stdout/stderr while grabbing stdout lines.
 
@@ -133,7 +209,11 @@ pub mod child_commands {
             day,
             part_1: None,
             part_2: None,
-            total_nanos: 0_f64,
+            source_hash: None,
+            part_1_memory: None,
+            part_2_memory: None,
+            part_1_counts: None,
+            part_2_counts: None,
         };
 
         output
@@ -143,71 +223,179 @@ pub mod child_commands {
                     return None;
                 }
 
-                let Some((timing_str, nanos)) = parse_time(l) else {
+                let Some(timing) = parse_time(l) else {
                     eprintln!("Could not parse timings from line: {l}");
                     return None;
                 };
 
                 let part = l.split(':').next()?;
-                Some((part, timing_str, nanos))
+                Some((part, timing))
             })
-            .for_each(|(part, timing_str, nanos)| {
+            .for_each(|(part, timing)| {
                 if part.contains("Part 1") {
-                    timings.part_1 = Some(timing_str.into());
+                    timings.part_1 = Some(timing);
                 } else if part.contains("Part 2") {
-                    timings.part_2 = Some(timing_str.into());
+                    timings.part_2 = Some(timing);
                 }
-
-                timings.total_nanos += nanos;
             });
 
         timings
     }
 
-    fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
-        s.split(postfix).next()?.parse().ok()
+    /// Parse the build profile printed by a timed solution.
+    pub fn parse_profile(output: &[String]) -> Option<String> {
+        output.iter().find_map(|line| {
+            let profile = line.split_once(PROFILE_LABEL)?.1;
+            Some(profile.trim_end_matches(ANSI_RESET).trim().to_string())
+        })
     }
 
-    fn parse_time(line: &str) -> Option<(&str, f64)> {
-        // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
-        let str_timing = line
-            .split(" samples)")
-            .next()?
-            .split('(')
-            .last()?
-            .split('@')
-            .next()?
-            .trim();
-
-        let parsed_timing = match str_timing {
-            s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
-            s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1000_f64),
-            s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
-            s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
-        }?;
-
-        Some((str_timing, parsed_timing))
+    /// Parse the heap stats printed by a solution built with the `dhat-heap` feature.
+    pub fn parse_memory(output: &[String], timing: &mut super::Timing) {
+        for line in output {
+            let Some((part, stats)) = line.split_once(':') else {
+                continue;
+            };
+
+            let Some(memory) = parse_memory_stats(stats) else {
+                continue;
+            };
+
+            if part.contains("Part 1") {
+                timing.part_1_memory = Some(memory);
+            } else if part.contains("Part 2") {
+                timing.part_2_memory = Some(memory);
+            }
+        }
     }
 
-    /// copied from: https://github.com/rust-lang/rust/blob/1.64.0/library/std/src/macros.rs#L328-L333
-    #[cfg(feature = "test_lib")]
-    macro_rules! assert_approx_eq {
-        ($a:expr, $b:expr) => {{
-            let (a, b) = (&$a, &$b);
-            assert!(
-                (*a - *b).abs() < 1.0e-6,
-                "{} is not approximately equal to {}",
-                *a,
-                *b
-            );
-        }};
+    fn parse_memory_stats(line: &str) -> Option<MemoryStats> {
+        // format: `[peak 1024 B, total 4096 B, 3 allocs]`
+        let stats = line
+            .rsplit_once(" [peak ")?
+            .1
+            .strip_suffix(" allocs]")?
+            .replace(" B", "");
+
+        let mut values = stats.split(", total ").flat_map(|s| s.split(", "));
+
+        Some(MemoryStats {
+            peak_bytes: values.next()?.parse().ok()?,
+            total_bytes: values.next()?.parse().ok()?,
+            allocations: values.next()?.parse().ok()?,
+        })
     }
 
-    #[cfg(feature = "test_lib")]
+    /// Parse the allocation counts printed by a solution built with the `count-alloc` feature.
+    pub fn parse_counts(output: &[String], timing: &mut super::Timing) {
+        for line in output {
+            let Some((part, stats)) = line.split_once(':') else {
+                continue;
+            };
+
+            let Some(counts) = parse_count_stats(stats) else {
+                continue;
+            };
+
+            if part.contains("Part 1") {
+                timing.part_1_counts = Some(counts);
+            } else if part.contains("Part 2") {
+                timing.part_2_counts = Some(counts);
+            }
+        }
+    }
+
+    fn parse_count_stats(line: &str) -> Option<Counts> {
+        // format: `{allocs 3, 4096 B}`
+        let stats = line.rsplit_once(" {allocs ")?.1.strip_suffix(" B}")?;
+        let (allocations, allocated_bytes) = stats.split_once(", ")?;
+
+        Some(Counts {
+            allocations: allocations.parse().ok()?,
+            allocated_bytes: allocated_bytes.parse().ok()?,
+            instructions: None,
+        })
+    }
+
+    /// Returns `true` if valgrind can be called to count instructions.
+    pub fn has_valgrind() -> bool {
+        Command::new("valgrind")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    /// Count the instructions of every part that has counts with cachegrind.
+    /// Reuses the binary built by the `counts` profile and runs it once per part.
+    pub fn count_instructions(day: Day, timing: &mut super::Timing) {
+        let target_dir = env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".into());
+        let bin = PathBuf::from(target_dir)
+            .join("counts")
+            .join(day.to_string());
+
+        for (part, counts) in [
+            (1, &mut timing.part_1_counts),
+            (2, &mut timing.part_2_counts),
+        ] {
+            let Some(counts) = counts else {
+                continue;
+            };
+
+            let output = Command::new("valgrind")
+                .args([
+                    "--tool=cachegrind",
+                    "--cache-sim=no",
+                    "--cachegrind-out-file=/dev/null",
+                ])
+                .arg(&bin)
+                .args(["--part", &part.to_string()])
+                .stdout(Stdio::null())
+                .output();
+
+            match output {
+                Ok(output) => {
+                    counts.instructions =
+                        parse_instructions(&String::from_utf8_lossy(&output.stderr));
+                }
+                Err(e) => eprintln!("Failed to run valgrind: {e}"),
+            }
+        }
+    }
+
+    fn parse_instructions(output: &str) -> Option<u64> {
+        // format: `==1234== I   refs:      1,234,567`
+        output
+            .lines()
+            .find_map(|line| line.split_once(" I")?.1.trim_start().strip_prefix("refs:"))
+            .and_then(|count| count.trim().replace(',', "").parse().ok())
+    }
+
+    fn parse_time(line: &str) -> Option<PartTiming> {
+        // format: `(74.13µs @ 100 samples)`, always the last parenthesis of the line.
+        let stats = line.strip_suffix(" samples)")?.rsplit_once('(')?.1;
+        let (duration, samples) = stats.split_once(" @ ")?;
+
+        Some(PartTiming {
+            nanos: parse_duration(duration)?,
+            samples: Some(samples.parse().ok()?),
+        })
+    }
+
+    #[cfg(test)]
     mod tests {
-        use super::parse_exec_time;
+        use std::time::Duration;
+
+        use proptest::prelude::*;
+
+        use super::{
+            parse_counts, parse_exec_time, parse_instructions, parse_memory, parse_profile,
+            parse_time,
+        };
 
         use crate::day;
+        use crate::template::{runner::PROFILE_LABEL, ANSI_ITALIC, ANSI_RESET};
 
         #[test]
         fn parses_execution_times() {
@@ -219,9 +407,30 @@ pub mod child_commands {
                 ],
                 day!(1),
             );
-            assert_approx_eq!(res.total_nanos, 74130074.13_f64);
-            assert_eq!(res.part_1.unwrap(), "74.13ns");
-            assert_eq!(res.part_2.unwrap(), "74.13ms");
+            assert_eq!(res.total_nanos(), 74_130_074);
+            assert_eq!(res.part_1.unwrap().nanos, 74);
+            assert_eq!(res.part_1.unwrap().samples, Some(100_000));
+            assert_eq!(res.part_2.unwrap().nanos, 74_130_000);
+            assert_eq!(res.part_2.unwrap().samples, Some(99_999));
+        }
+
+        proptest! {
+            /// `parse_time` reads what the runner prints, which is rounded to a tenth of the unit.
+            #[test]
+            fn parse_time_inverts_runner_output(nanos in any::<u64>(), samples in any::<u64>()) {
+                let duration = Duration::from_nanos(nanos);
+                let line = format!("Part 1: 42 ({duration:.1?} @ {samples} samples)");
+                let timing = parse_time(&line).unwrap();
+
+                let unit: u64 = match nanos {
+                    0..1_000 => 1,
+                    1_000..1_000_000 => 1_000,
+                    1_000_000..1_000_000_000 => 1_000_000,
+                    _ => 1_000_000_000,
+                };
+                prop_assert!(timing.nanos.abs_diff(nanos) * 20 <= unit);
+                prop_assert_eq!(timing.samples, Some(samples));
+            }
         }
 
         #[test]
@@ -234,9 +443,62 @@ pub mod child_commands {
                 ],
                 day!(1),
             );
-            assert_approx_eq!(res.total_nanos, 2100000000_f64);
-            assert_eq!(res.part_1.unwrap(), "2s");
-            assert_eq!(res.part_2.unwrap(), "100ms");
+            assert_eq!(res.total_nanos(), 2_100_000_000);
+            assert_eq!(res.part_1.unwrap().nanos, 2_000_000_000);
+            assert_eq!(res.part_2.unwrap().nanos, 100_000_000);
+        }
+
+        #[test]
+        fn parses_memory_stats() {
+            let mut timing = parse_exec_time(&[], day!(1));
+            parse_memory(
+                &[
+                    "Part 1: 10 (1.2ms) [peak 1024 B, total 4096 B, 3 allocs]".into(),
+                    "Part 2: ✖        ".into(),
+                ],
+                &mut timing,
+            );
+            let memory = timing.part_1_memory.unwrap();
+            assert_eq!(memory.peak_bytes, 1024);
+            assert_eq!(memory.total_bytes, 4096);
+            assert_eq!(memory.allocations, 3);
+            assert_eq!(timing.part_2_memory, None);
+        }
+
+        #[test]
+        fn parses_build_profile() {
+            let output = [
+                format!("{ANSI_ITALIC}{PROFILE_LABEL}dev{ANSI_RESET}"),
+                "Part 1: 10 (1.2ms @ 100 samples)".into(),
+            ];
+            assert_eq!(parse_profile(&output), Some("dev".into()));
+            assert_eq!(parse_profile(&output[1..]), None);
+        }
+
+        #[test]
+        fn parses_counts() {
+            let mut timing = parse_exec_time(&[], day!(1));
+            parse_counts(
+                &[
+                    "Part 1: ✖        ".into(),
+                    "Part 2: 10 (1.2ms) {allocs 3, 4096 B}".into(),
+                ],
+                &mut timing,
+            );
+            let counts = timing.part_2_counts.unwrap();
+            assert_eq!(counts.allocations, 3);
+            assert_eq!(counts.allocated_bytes, 4096);
+            assert_eq!(counts.instructions, None);
+            assert_eq!(timing.part_1_counts, None);
+        }
+
+        #[test]
+        fn parses_instruction_counts() {
+            let output = "==42== Cachegrind, a high-precision tracing profiler\n==42== \n==42== I refs:        1,234,567\n";
+            assert_eq!(parse_instructions(output), Some(1_234_567));
+            let output = "==42== I   refs:      987\n";
+            assert_eq!(parse_instructions(output), Some(987));
+            assert_eq!(parse_instructions("==42== error"), None);
         }
 
         #[test]
@@ -249,9 +511,9 @@ pub mod child_commands {
                 ],
                 day!(1),
             );
-            assert_approx_eq!(res.total_nanos, 0_f64);
-            assert_eq!(res.part_1.is_none(), true);
-            assert_eq!(res.part_2.is_none(), true);
+            assert_eq!(res.total_nanos(), 0);
+            assert!(res.part_1.is_none());
+            assert!(res.part_2.is_none());
         }
     }
 }
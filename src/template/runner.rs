@@ -1,55 +1,322 @@
 /// Encapsulates code that interacts with solution functions.
 use std::fmt::Display;
 use std::hint::black_box;
-use std::io::{stdout, Write};
-use std::process::Output;
+use std::io::{stdin, stdout, Read, Write};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
-use std::{cmp, env, process};
+use std::{cmp, fs, process};
 
+use clap::Parser;
+
+use crate::template::bitmap::Bitmap;
+#[cfg(feature = "count-alloc")]
+use crate::template::timings::Counts;
+use crate::template::timings::MemoryStats;
 use crate::template::ANSI_BOLD;
-use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
+use crate::template::{aoc_cli, config, data_dir, inputs, read_file, Day, ANSI_ITALIC, ANSI_RESET};
+
+/// Options accepted by a solution binary. These are passed by the `solve`, `all` and `time` commands.
+#[derive(Parser, Debug, Default, Clone, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Bench each part instead of running it once.
+    #[arg(long)]
+    pub time: bool,
+
+    /// Submit the result of the given part via aoc-cli.
+    #[arg(
+        long,
+        value_name = "PART",
+        value_parser = clap::value_parser!(u8).range(1..=2),
+        conflicts_with_all = ["part", "input", "example"]
+    )]
+    pub submit: Option<u8>,
+
+    /// Only run the given part.
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: Option<u8>,
+
+    /// Read the input from this file instead of the day's input file. `-` reads from stdin.
+    #[arg(long, value_name = "PATH", conflicts_with = "example")]
+    pub input: Option<PathBuf>,
+
+    /// Use the day's example file as input. A name selects `DD-NAME.txt` instead of `DD.txt`.
+    #[arg(long, value_name = "NAME", num_args = 0..=1, default_missing_value = "")]
+    pub example: Option<String>,
+
+    /// Print a diagnosis of the input instead of running the parts, for days that provide one.
+    #[arg(long, conflicts_with_all = ["time", "submit", "part"])]
+    pub explain: bool,
+
+    /// Start an interactive session on the input instead of running the parts, for days that provide one.
+    #[arg(long, conflicts_with_all = ["time", "submit", "part", "explain"])]
+    pub interactive: bool,
+
+    /// Arguments of the interactive session, e.g. `cargo solve 15 --interactive -- --help`.
+    #[arg(last = true, requires = "interactive")]
+    pub args: Vec<String>,
+
+    /// Draw a picture of the input into this file instead of running the parts, for days that provide one.
+    /// Files ending in `.pbm` are PBM images, other files and `-` (stdout) get ASCII art.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["time", "submit", "part", "explain", "interactive"])]
+    pub render: Option<PathBuf>,
+}
+
+impl RunOptions {
+    /// The arguments that make a solution binary parse these options, for commands that run it with cargo.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        if self.time {
+            args.push("--time".to_string());
+        }
 
-pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+        if let Some(submit) = self.submit {
+            args.push("--submit".to_string());
+            args.push(submit.to_string());
+        }
+
+        if let Some(part) = self.part {
+            args.push("--part".to_string());
+            args.push(part.to_string());
+        }
+
+        if let Some(input) = &self.input {
+            args.push("--input".to_string());
+            args.push(input.display().to_string());
+        }
+
+        if let Some(example) = &self.example {
+            // `=` keeps an empty name from being read as a missing value.
+            args.push(format!("--example={example}"));
+        }
+
+        if self.explain {
+            args.push("--explain".to_string());
+        }
+
+        if let Some(render) = &self.render {
+            args.push("--render".to_string());
+            args.push(render.display().to_string());
+        }
+
+        if self.interactive {
+            args.push("--interactive".to_string());
+            args.push("--".to_string());
+            args.extend_from_slice(&self.args);
+        }
+
+        args
+    }
+
+    /// Returns `true` if the given part should be run.
+    pub fn runs_part(&self, part: u8) -> bool {
+        self.part.is_none_or(|p| p == part)
+    }
+
+    /// Reads the input selected by `--input` or `--example`, defaulting to the day's input file.
+    pub fn read_input(&self, day: Day) -> String {
+        let path = match (&self.input, &self.example) {
+            (Some(path), _) if path.as_os_str() == "-" => {
+                let mut input = String::new();
+                if let Err(e) = stdin().read_to_string(&mut input) {
+                    eprintln!("Failed to read input from stdin: {e}");
+                    process::exit(1);
+                }
+                return input;
+            }
+            (Some(path), _) => path.clone(),
+            (None, Some(name)) if !name.is_empty() => {
+                data_dir("examples").join(format!("{day}-{name}.txt"))
+            }
+            (None, Some(_)) => return read_file("examples", day),
+            (None, None) => {
+                let input = read_file("inputs", day);
+                inputs::warn_if_modified(day, &input);
+                return input;
+            }
+        };
+
+        fs::read_to_string(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to read input file \"{}\": {e}", path.display());
+            process::exit(1);
+        })
+    }
+}
+
+pub fn run_part<I: Clone, T: Display>(
+    func: impl Fn(I) -> Option<T>,
+    input: I,
+    day: Day,
+    part: u8,
+    options: &RunOptions,
+) {
     let part_str = format!("Part {part}");
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let (result, duration, samples, memory) =
+        run_timed(&func, input.clone(), part, options.time, |result| {
+            print_result(result, &part_str, "");
+        });
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    let mut stats_str = format_duration(&duration, samples);
+    if let Some(memory) = memory {
+        stats_str.push_str(&format_memory(&memory));
+    }
+
+    #[cfg(feature = "count-alloc")]
+    {
+        let input = input.clone();
+        let (_, counts) = super::alloc_counter::count(|| func(input));
+        stats_str.push_str(&format_counts(&counts));
+    }
+
+    print_result(&result, &part_str, &stats_str);
+
+    #[cfg(feature = "pprof-cpu")]
+    match super::cpu_profile::profile_part(&func, input, day, part) {
+        Ok(profile) => println!(
+            "{part_str}: {ANSI_ITALIC}flamegraph written to {} ({} iterations, folded stacks in {}){ANSI_RESET}",
+            profile.flamegraph.display(),
+            profile.iterations,
+            profile.folded.display()
+        ),
+        Err(e) => eprintln!("{part_str}: {e}"),
+    }
 
     if let Some(result) = result {
-        submit_result(result, day, part);
+        if options.submit == Some(part) {
+            if let Err(e) = submit_result(result, day, part) {
+                eprintln!("failed to call aoc-cli: {e}");
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Prefix of the line with the build profile, printed by timed runs.
+pub const PROFILE_LABEL: &str = "Profile: ";
+
+/// The cargo profile this binary was built with. Profiles inheriting from `release`, like `dhat`,
+/// are reported as `release`, and any profile with debug assertions as `dev`.
+pub fn build_profile() -> &'static str {
+    if cfg!(debug_assertions) {
+        "dev"
+    } else {
+        "release"
+    }
+}
+
+/// Prints the build profile, so that `cargo time` records the profile the timings were taken in.
+pub fn print_build_profile() {
+    println!(
+        "{ANSI_ITALIC}{PROFILE_LABEL}{}{ANSI_RESET}",
+        build_profile()
+    );
+}
+
+/// Modes of a solution binary besides running its parts, added with [`crate::solution`].
+#[derive(Default)]
+pub struct Hooks {
+    /// Returns a diagnosis of the input, printed by `--explain`.
+    pub explain: Option<fn(&str) -> String>,
+    /// Runs an interactive session on the input for `--interactive`, with the arguments after `--`.
+    pub interactive: Option<fn(&str, &[String])>,
+    /// Draws a picture of the input for `--render`.
+    pub render: Option<fn(&str) -> Bitmap>,
+}
+
+impl Hooks {
+    /// Runs the mode selected in `options`. Returns `false` if the parts should be run instead.
+    pub fn run(&self, options: &RunOptions, input: &str, day: Day) -> bool {
+        let missing = |flag: &str, hook: &str| -> ! {
+            eprintln!(
+                "Day {day} has no `{flag}` mode. Add one with `solution!({}, {hook} = ...)`.",
+                day.into_inner()
+            );
+            process::exit(1);
+        };
+
+        if options.explain {
+            let explain = self
+                .explain
+                .unwrap_or_else(|| missing("--explain", "explain"));
+            print!("{}", explain(input));
+            true
+        } else if options.interactive {
+            let interactive = self
+                .interactive
+                .unwrap_or_else(|| missing("--interactive", "interactive"));
+            interactive(input, &options.args);
+            true
+        } else if let Some(path) = &options.render {
+            let render = self.render.unwrap_or_else(|| missing("--render", "render"));
+            if let Err(e) = render(input).write(path) {
+                eprintln!("Failed to write \"{}\": {e}", path.display());
+                process::exit(1);
+            }
+            if path.as_os_str() != "-" {
+                println!("Wrote the picture to \"{}\".", path.display());
+            }
+            true
+        } else {
+            false
+        }
     }
 }
 
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
 ///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+///     The budget and sample bounds can be changed in the `[bench]` section of `aoc.toml`.
+///
+/// With the `dhat-heap` feature, heap usage of the first execution is profiled as well.
 fn run_timed<I: Clone, T>(
     func: impl Fn(I) -> T,
     input: I,
+    part: u8,
+    is_timed: bool,
     hook: impl Fn(&T),
-) -> (T, Duration, u128) {
+) -> (T, Duration, u128, Option<MemoryStats>) {
     let timer = Instant::now();
-    let result = {
+    let (result, memory) = {
         let input = input.clone();
-
-        #[cfg(feature = "dhat-heap")]
-        let _profiler = dhat::Profiler::new_heap();
-
-        func(input)
+        run_profiled(|| func(input), part)
     };
     let base_time = timer.elapsed();
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
+    let run = if is_timed {
         bench(func, input, &base_time)
     } else {
         (base_time, 1)
     };
 
-    (result, run.0, run.1)
+    (result, run.0, run.1, memory)
+}
+
+/// Run `func` under the dhat heap profiler, writing its report to `dhat-heap-{part}.json`.
+#[cfg(feature = "dhat-heap")]
+fn run_profiled<T>(func: impl FnOnce() -> T, part: u8) -> (T, Option<MemoryStats>) {
+    let profiler = dhat::Profiler::builder()
+        .file_name(format!("dhat-heap-{part}.json"))
+        .build();
+
+    let result = func();
+    let stats = dhat::HeapStats::get();
+    drop(profiler);
+
+    let memory = MemoryStats {
+        peak_bytes: stats.max_bytes as u64,
+        total_bytes: stats.total_bytes,
+        allocations: stats.total_blocks,
+    };
+
+    (result, Some(memory))
+}
+
+#[cfg(not(feature = "dhat-heap"))]
+fn run_profiled<T>(func: impl FnOnce() -> T, _part: u8) -> (T, Option<MemoryStats>) {
+    (func(), None)
 }
 
 fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
@@ -58,8 +325,13 @@ fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) ->
     print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
     let _ = stdout.flush();
 
-    let bench_iterations =
-        (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000);
+    let config = config::get();
+    let min_samples = config.bench_min_samples.value;
+    let max_samples = cmp::max(config.bench_max_samples.value, min_samples);
+
+    let bench_iterations = (Duration::from_millis(config.bench_target_ms.value).as_nanos()
+        / cmp::max(base_time.as_nanos(), 10))
+    .clamp(min_samples, max_samples);
 
     let mut timers: Vec<Duration> = vec![];
 
@@ -94,6 +366,21 @@ fn format_duration(duration: &Duration, samples: u128) -> String {
     }
 }
 
+fn format_memory(memory: &MemoryStats) -> String {
+    format!(
+        " [peak {} B, total {} B, {} allocs]",
+        memory.peak_bytes, memory.total_bytes, memory.allocations
+    )
+}
+
+#[cfg(feature = "count-alloc")]
+fn format_counts(counts: &Counts) -> String {
+    format!(
+        " {{allocs {}, {} B}}",
+        counts.allocations, counts.allocated_bytes
+    )
+}
+
 fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
     let is_intermediate_result = duration_str.is_empty();
 
@@ -129,41 +416,75 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
     }
 }
 
-/// Parse the arguments passed to `solve` and try to submit one part of the solution if:
-///  1. we are in `--release` mode.
-///  2. aoc-cli is installed.
+/// Try to submit one part of the solution if aoc-cli is installed.
 fn submit_result<T: Display>(
     result: T,
     day: Day,
     part: u8,
-) -> Option<Result<Output, aoc_cli::AocCommandError>> {
-    let args: Vec<String> = env::args().collect();
-
-    if !args.contains(&"--submit".into()) {
-        return None;
-    }
-
-    if args.len() < 3 {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
+) -> Result<String, aoc_cli::AocCommandError> {
+    if aoc_cli::check().is_err() {
+        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
         process::exit(1);
     }
 
-    let part_index = args.iter().position(|x| x == "--submit").unwrap() + 1;
+    println!("Submitting result via aoc-cli...");
+    aoc_cli::submit(day, part, &result.to_string())
+}
+
+/* -------------------------------------------------------------------------- */
 
-    let Ok(part_submit) = args[part_index].parse::<u8>() else {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
-        process::exit(1);
-    };
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
 
-    if part_submit != part {
-        return None;
+    use super::RunOptions;
+
+    fn round_trip(options: &RunOptions) -> RunOptions {
+        let args = std::iter::once("01".to_string()).chain(options.to_args());
+        RunOptions::try_parse_from(args).unwrap()
     }
 
-    if aoc_cli::check().is_err() {
-        eprintln!("command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.");
-        process::exit(1);
+    #[test]
+    fn passes_options_as_arguments() {
+        let options = [
+            RunOptions::default(),
+            RunOptions {
+                time: true,
+                part: Some(2),
+                example: Some(String::new()),
+                ..RunOptions::default()
+            },
+            RunOptions {
+                submit: Some(1),
+                ..RunOptions::default()
+            },
+            RunOptions {
+                input: Some("data/my input.txt".into()),
+                render: Some("-".into()),
+                ..RunOptions::default()
+            },
+            RunOptions {
+                example: Some("larger".to_string()),
+                explain: true,
+                ..RunOptions::default()
+            },
+            RunOptions {
+                interactive: true,
+                args: vec!["--replay".to_string(), "--".to_string()],
+                ..RunOptions::default()
+            },
+        ];
+
+        for options in options {
+            assert_eq!(round_trip(&options), options);
+        }
     }
 
-    println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    #[test]
+    fn rejects_submit_with_part() {
+        for part in ["1", "2"] {
+            let args = ["01", "--submit", "2", "--part", part];
+            assert!(RunOptions::try_parse_from(args).is_err());
+        }
+    }
 }
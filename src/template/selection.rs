@@ -0,0 +1,191 @@
+/// Selection of the days (and parts) that the `all` and `time` commands run.
+use std::{cmp::Reverse, fs};
+
+use clap::Args;
+
+use crate::template::{all_days, run_multi::get_path_for_bin, timings::Timings, Day, Days};
+
+/// Command-line selectors shared by the `all` and `time` commands.
+/// All given selectors are combined, i.e. a day has to match every one of them.
+#[derive(Args, Clone, Debug, Default)]
+pub struct DaySelector {
+    /// Days to run, e.g. `3`, `1..10` or `3,5,7`. Defaults to all days.
+    pub days: Option<Days>,
+
+    /// Only days without stored timings for both parts.
+    #[arg(long)]
+    pub unsolved: bool,
+
+    /// Only days whose solution changed since their timings were stored.
+    #[arg(long)]
+    pub changed: bool,
+
+    /// Only the N slowest days, according to the stored timings.
+    #[arg(long, value_name = "N")]
+    pub slowest: Option<usize>,
+
+    /// Only run the given part.
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: Option<u8>,
+}
+
+/// The resolved days and parts to run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    pub days: Days,
+    pub part: Option<u8>,
+}
+
+impl DaySelector {
+    /// Returns `true` if any selector narrowing down the days was passed.
+    pub fn is_filtered(&self) -> bool {
+        self.days.is_some() || self.unsolved || self.changed || self.slowest.is_some()
+    }
+
+    /// Resolves the selectors against the stored timings.
+    pub fn select(&self, stored_timings: &Timings) -> Selection {
+        self.select_with(stored_timings, source_hash)
+    }
+
+    fn select_with(
+        &self,
+        stored_timings: &Timings,
+        source_hash: impl Fn(Day) -> Option<String>,
+    ) -> Selection {
+        let mut days: Vec<Day> = match &self.days {
+            Some(days) => days.iter().collect(),
+            None => all_days().collect(),
+        };
+
+        if self.unsolved {
+            days.retain(|day| !stored_timings.is_day_complete(*day));
+        }
+
+        if self.changed {
+            days.retain(|day| {
+                let stored_hash = stored_timings.get(*day).and_then(|t| t.source_hash.clone());
+                stored_hash.is_none() || stored_hash != source_hash(*day)
+            });
+        }
+
+        if let Some(n) = self.slowest {
+            let nanos = |day: &Day| -> u64 {
+                stored_timings.get(*day).map_or(0, |t| match self.part {
+                    Some(part) => t.part_nanos(part).unwrap_or_default(),
+                    None => t.total_nanos(),
+                })
+            };
+
+            days.retain(|day| stored_timings.get(*day).is_some());
+            days.sort_by_key(|day| Reverse(nanos(day)));
+            days.truncate(n);
+        }
+
+        Selection {
+            days: days.into_iter().collect(),
+            part: self.part,
+        }
+    }
+}
+
+/// Hashes the source of a day's solution. Returns `None` if the day has not been scaffolded.
+pub fn source_hash(day: Day) -> Option<String> {
+    let source = fs::read(get_path_for_bin(day)).ok()?;
+    Some(format!("{:016x}", fxhash::hash64(&source)))
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::DaySelector;
+    use crate::{
+        day,
+        template::{
+            timings::{parse_duration, PartTiming, Timing, Timings},
+            Day,
+        },
+    };
+
+    fn part_timing(duration: &str) -> PartTiming {
+        PartTiming {
+            nanos: parse_duration(duration).unwrap(),
+            samples: Some(10),
+        }
+    }
+
+    fn timing(day: Day, part_1: Option<&str>, part_2: Option<&str>, hash: &str) -> Timing {
+        Timing {
+            day,
+            part_1: part_1.map(part_timing),
+            part_2: part_2.map(part_timing),
+            source_hash: Some(hash.into()),
+            part_1_memory: None,
+            part_2_memory: None,
+            part_1_counts: None,
+            part_2_counts: None,
+        }
+    }
+
+    fn get_mock_timings() -> Timings {
+        Timings {
+            data: vec![
+                timing(day!(1), Some("10ms"), Some("20ms"), "a"),
+                timing(day!(2), Some("30ms"), Some("1ms"), "a"),
+                timing(day!(3), Some("5ms"), None, "c"),
+            ],
+            environment: None,
+        }
+    }
+
+    fn select(selector: &DaySelector) -> Vec<u8> {
+        selector
+            .select_with(&get_mock_timings(), |_| Some("a".into()))
+            .days
+            .iter()
+            .map(Day::into_inner)
+            .collect()
+    }
+
+    #[test]
+    fn selects_all_days_by_default() {
+        assert_eq!(select(&DaySelector::default()).len(), 25);
+    }
+
+    #[test]
+    fn selects_unsolved_days_in_range() {
+        let selector = DaySelector {
+            days: Some("1..4".parse().unwrap()),
+            unsolved: true,
+            ..DaySelector::default()
+        };
+        assert_eq!(select(&selector), vec![3, 4]);
+    }
+
+    #[test]
+    fn selects_changed_days() {
+        let selector = DaySelector {
+            days: Some("1..4".parse().unwrap()),
+            changed: true,
+            ..DaySelector::default()
+        };
+        // day 3's hash differs, day 4 has no stored timing.
+        assert_eq!(select(&selector), vec![3, 4]);
+    }
+
+    #[test]
+    fn selects_slowest_days() {
+        let selector = DaySelector {
+            slowest: Some(2),
+            ..DaySelector::default()
+        };
+        assert_eq!(select(&selector), vec![1, 2]);
+
+        let selector = DaySelector {
+            slowest: Some(1),
+            part: Some(2),
+            ..DaySelector::default()
+        };
+        assert_eq!(select(&selector), vec![1]);
+    }
+}
@@ -0,0 +1,435 @@
+/// Spaces out requests to the puzzle server, see <https://www.reddit.com/r/adventofcode/wiki/faqs/automation>.
+/// Every request waits for `throttle.min_interval_ms` since the previous one, across invocations of the CLI.
+/// Submissions also wait out the cooldown the server announces after a wrong answer,
+/// and are retried if the server rejected them for being sent too early.
+use std::{
+    collections::HashMap,
+    fs,
+    str::FromStr,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tinyjson::JsonValue;
+
+use crate::template::config;
+
+/// Sends requests to the puzzle server. Implemented by aoc-cli, and by a fake server in tests.
+pub trait PuzzleServer {
+    type Error;
+
+    /// Sends a request and returns the text of the response.
+    fn send(&mut self, args: &[String]) -> Result<String, Self::Error>;
+}
+
+/// Time source of the scheduler, so that tests do not have to sleep.
+pub trait Clock {
+    /// Time since the Unix epoch.
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RequestKind {
+    /// Downloading inputs or puzzle descriptions.
+    Fetch,
+    Submit,
+}
+
+/// A wait announced by the server in response to a submission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wait {
+    /// The answer was not checked because the previous one was sent too recently.
+    TooRecent(Duration),
+    /// The answer was wrong, and the next one may only be sent after this cooldown.
+    Cooldown(Duration),
+}
+
+/// Parses the wait from a submission response, e.g. `You have 1m 5s left to wait.`
+/// or `please wait 5 minutes before trying again.`
+pub fn parse_wait(response: &str) -> Option<Wait> {
+    if let Some((_, rest)) = response.split_once("You have ") {
+        let wait = rest.split_once(" left to wait")?.0;
+        let seconds = wait
+            .split_whitespace()
+            .map(|part| {
+                let (value, unit) = part.split_at(part.find(|c: char| !c.is_ascii_digit())?);
+                let value: u64 = value.parse().ok()?;
+                match unit {
+                    "h" => Some(value * 3600),
+                    "m" => Some(value * 60),
+                    "s" => Some(value),
+                    _ => None,
+                }
+            })
+            .sum::<Option<u64>>()?;
+        return Some(Wait::TooRecent(Duration::from_secs(seconds)));
+    }
+
+    let wait = response
+        .split_once("please wait ")?
+        .1
+        .split_once(" before trying again")?
+        .0;
+    let (count, unit) = wait.split_once(' ')?;
+    let count = match count {
+        "one" => 1,
+        count => count.parse().ok()?,
+    };
+    let seconds = match unit {
+        "minute" | "minutes" => count * 60,
+        "second" | "seconds" => count,
+        _ => return None,
+    };
+    Some(Wait::Cooldown(Duration::from_secs(seconds)))
+}
+
+/// Request timestamps in milliseconds since the Unix epoch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThrottleState {
+    pub last_request: u64,
+    /// The earliest time the next answer may be submitted.
+    pub submit_not_before: u64,
+}
+
+impl ThrottleState {
+    /// Reads the state file, `paths.throttle`. If not present, no request was sent yet.
+    pub fn read_from_file() -> Self {
+        fs::read_to_string(&config::get().throttle_file.value)
+            .ok()
+            .and_then(|s| JsonValue::from_str(&s).ok())
+            .and_then(|json| ThrottleState::try_from(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn store_file(&self) -> Result<(), std::io::Error> {
+        let json = JsonValue::from(self);
+        let path = &config::get().throttle_file.value;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(path)?;
+        json.format_to(&mut file)
+    }
+}
+
+/// Settings of the scheduler, see the `[throttle]` section of `aoc.toml`.
+#[derive(Clone, Copy, Debug)]
+pub struct Throttle {
+    pub min_interval: Duration,
+    /// How often a submission that was sent too early is retried.
+    pub max_retries: u32,
+}
+
+impl Throttle {
+    pub fn from_config() -> Self {
+        let config = config::get();
+        Throttle {
+            min_interval: Duration::from_millis(config.throttle_min_interval_ms.value),
+            max_retries: config.throttle_max_retries.value,
+        }
+    }
+
+    /// Sends a request once the throttle allows it, and updates `state`.
+    pub fn send<S: PuzzleServer>(
+        &self,
+        server: &mut S,
+        clock: &dyn Clock,
+        state: &mut ThrottleState,
+        kind: RequestKind,
+        args: &[String],
+    ) -> Result<String, S::Error> {
+        let mut retries = 0;
+
+        loop {
+            let mut not_before = state.last_request + millis(self.min_interval);
+            if kind == RequestKind::Submit {
+                not_before = not_before.max(state.submit_not_before);
+            }
+
+            let now = millis(clock.now());
+            if not_before > now {
+                let wait = Duration::from_millis(not_before - now);
+                if wait >= Duration::from_secs(1) {
+                    println!(
+                        "Waiting {} before contacting the server...",
+                        format_wait(wait)
+                    );
+                }
+                clock.sleep(wait);
+            }
+
+            state.last_request = millis(clock.now());
+            let response = server.send(args)?;
+
+            if kind == RequestKind::Fetch {
+                return Ok(response);
+            }
+
+            match parse_wait(&response) {
+                Some(Wait::TooRecent(wait)) => {
+                    state.submit_not_before = state.last_request + millis(wait);
+                    if retries >= self.max_retries {
+                        return Ok(response);
+                    }
+                    retries += 1;
+                    println!(
+                        "Answer was sent too early, retrying in {} ({retries}/{}).",
+                        format_wait(wait),
+                        self.max_retries
+                    );
+                }
+                Some(Wait::Cooldown(wait)) => {
+                    state.submit_not_before = state.last_request + millis(wait);
+                    return Ok(response);
+                }
+                None => return Ok(response),
+            }
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// Formats a wait with second precision, e.g. `1h 2m 5s`.
+pub fn format_wait(duration: Duration) -> String {
+    let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s}s"),
+        (h, m, s) => format!("{h}h {m}m {s}s"),
+    }
+}
+
+/// Sends a request through the throttle, persisting its state.
+pub fn send<S: PuzzleServer>(
+    server: &mut S,
+    kind: RequestKind,
+    args: &[String],
+) -> Result<String, S::Error> {
+    let mut state = ThrottleState::read_from_file();
+    let result = Throttle::from_config().send(server, &SystemClock, &mut state, kind, args);
+
+    if let Err(e) = state.store_file() {
+        eprintln!("Failed to store the request timestamps: {e}");
+    }
+
+    result
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&ThrottleState> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &ThrottleState) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert(
+            "last_request".into(),
+            JsonValue::Number(value.last_request as f64),
+        );
+        map.insert(
+            "submit_not_before".into(),
+            JsonValue::Number(value.submit_not_before as f64),
+        );
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for ThrottleState {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected throttle state to be a JSON object.")?;
+
+        let get_number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .map(|v| v as u64)
+                .unwrap_or_default()
+        };
+
+        Ok(ThrottleState {
+            last_request: get_number("last_request"),
+            submit_not_before: get_number("submit_not_before"),
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, collections::VecDeque, time::Duration};
+
+    use super::{
+        format_wait, parse_wait, Clock, PuzzleServer, RequestKind, Throttle, ThrottleState, Wait,
+    };
+    use crate::template::aoc_cli::AocCommandError;
+
+    /// Advances instantly when sleeping.
+    struct FakeClock(Cell<Duration>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.0.set(self.0.get() + duration);
+        }
+    }
+
+    /// Answers with scripted responses and records when requests arrived.
+    struct FakeServer<'a> {
+        clock: &'a FakeClock,
+        responses: VecDeque<&'static str>,
+        received: Vec<Duration>,
+    }
+
+    impl PuzzleServer for FakeServer<'_> {
+        type Error = AocCommandError;
+
+        fn send(&mut self, _args: &[String]) -> Result<String, AocCommandError> {
+            self.received.push(self.clock.now());
+            Ok(self.responses.pop_front().unwrap_or("ok").into())
+        }
+    }
+
+    const TOO_RECENT: &str = "You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 5s left to wait. [Return to Day 1]";
+    const WRONG: &str = "That's not the right answer.  If you're stuck, make sure you're using the full input data; please wait one minute before trying again. [Return to Day 1]";
+
+    fn setup(responses: &[&'static str]) -> (FakeClock, VecDeque<&'static str>, Throttle) {
+        let clock = FakeClock(Cell::new(Duration::from_secs(1_000)));
+        let throttle = Throttle {
+            min_interval: Duration::from_secs(5),
+            max_retries: 2,
+        };
+        (clock, responses.iter().copied().collect(), throttle)
+    }
+
+    #[test]
+    fn parses_waits() {
+        assert_eq!(
+            parse_wait(TOO_RECENT),
+            Some(Wait::TooRecent(Duration::from_secs(65)))
+        );
+        assert_eq!(
+            parse_wait("You have 42s left to wait."),
+            Some(Wait::TooRecent(Duration::from_secs(42)))
+        );
+        assert_eq!(
+            parse_wait(WRONG),
+            Some(Wait::Cooldown(Duration::from_secs(60)))
+        );
+        assert_eq!(
+            parse_wait("please wait 5 minutes before trying again."),
+            Some(Wait::Cooldown(Duration::from_secs(300)))
+        );
+        assert_eq!(parse_wait("That's the right answer!"), None);
+    }
+
+    #[test]
+    fn spaces_out_requests() {
+        let (clock, responses, throttle) = setup(&[]);
+        let mut server = FakeServer {
+            clock: &clock,
+            responses,
+            received: vec![],
+        };
+        let mut state = ThrottleState::default();
+
+        for _ in 0..3 {
+            throttle
+                .send(&mut server, &clock, &mut state, RequestKind::Fetch, &[])
+                .unwrap();
+        }
+
+        let secs: Vec<u64> = server.received.iter().map(Duration::as_secs).collect();
+        assert_eq!(secs, [1_000, 1_005, 1_010]);
+    }
+
+    #[test]
+    fn retries_submissions_sent_too_early() {
+        let (clock, responses, throttle) = setup(&[TOO_RECENT, "That's the right answer!"]);
+        let mut server = FakeServer {
+            clock: &clock,
+            responses,
+            received: vec![],
+        };
+        let mut state = ThrottleState::default();
+
+        let response = throttle
+            .send(&mut server, &clock, &mut state, RequestKind::Submit, &[])
+            .unwrap();
+
+        assert_eq!(response, "That's the right answer!");
+        let secs: Vec<u64> = server.received.iter().map(Duration::as_secs).collect();
+        assert_eq!(secs, [1_000, 1_065]);
+    }
+
+    #[test]
+    fn gives_up_after_max_retries() {
+        let (clock, responses, throttle) = setup(&[TOO_RECENT, TOO_RECENT, TOO_RECENT]);
+        let mut server = FakeServer {
+            clock: &clock,
+            responses,
+            received: vec![],
+        };
+        let mut state = ThrottleState::default();
+
+        let response = throttle
+            .send(&mut server, &clock, &mut state, RequestKind::Submit, &[])
+            .unwrap();
+
+        assert_eq!(response, TOO_RECENT);
+        assert_eq!(server.received.len(), 3);
+    }
+
+    #[test]
+    fn waits_out_cooldown_before_next_submission() {
+        let (clock, responses, throttle) = setup(&[WRONG]);
+        let mut server = FakeServer {
+            clock: &clock,
+            responses,
+            received: vec![],
+        };
+        let mut state = ThrottleState::default();
+
+        for kind in [RequestKind::Submit, RequestKind::Fetch, RequestKind::Submit] {
+            throttle
+                .send(&mut server, &clock, &mut state, kind, &[])
+                .unwrap();
+        }
+
+        // fetching is not affected by the cooldown of submissions.
+        let secs: Vec<u64> = server.received.iter().map(Duration::as_secs).collect();
+        assert_eq!(secs, [1_000, 1_005, 1_060]);
+    }
+
+    #[test]
+    fn formats_waits() {
+        assert_eq!(format_wait(Duration::from_millis(4_200)), "5s");
+        assert_eq!(format_wait(Duration::from_secs(65)), "1m 5s");
+        assert_eq!(format_wait(Duration::from_secs(3_725)), "1h 2m 5s");
+    }
+}
@@ -1,40 +1,327 @@
-use std::{collections::HashMap, fs, io::Error, str::FromStr};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    fs,
+    io::Error,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tinyjson::JsonValue;
 
-use crate::template::Day;
+use crate::template::{config, environment::Environment, Day};
 
-static TIMINGS_FILE_PATH: &str = "./data/timings.json";
+/// Version of the JSON schema written by [`Timings::store_file`].
+/// Version 1 stored part timings as `Duration` debug strings (e.g. `"74.1µs"`) and is migrated on read.
+pub const TIMINGS_VERSION: u32 = 2;
 
 /// Represents benchmark times for a single day.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Timing {
     pub day: Day,
-    pub part_1: Option<String>,
-    pub part_2: Option<String>,
-    pub total_nanos: f64,
+    pub part_1: Option<PartTiming>,
+    pub part_2: Option<PartTiming>,
+    /// Hash of the solution source at the time of the run, used to detect changed solutions.
+    pub source_hash: Option<String>,
+    pub part_1_memory: Option<MemoryStats>,
+    pub part_2_memory: Option<MemoryStats>,
+    pub part_1_counts: Option<Counts>,
+    pub part_2_counts: Option<Counts>,
+}
+
+/// Benchmark result of a single solution part.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartTiming {
+    /// Mean duration of a single execution in nanoseconds.
+    pub nanos: u64,
+    /// Number of executions the mean was taken over. `None` for timings migrated from version 1.
+    pub samples: Option<u64>,
+}
+
+impl PartTiming {
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(self.nanos)
+    }
+}
+
+impl Display for PartTiming {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1?}", self.duration())
+    }
+}
+
+/// Heap usage of a single execution of a solution part, as recorded by dhat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub peak_bytes: u64,
+    pub total_bytes: u64,
+    pub allocations: u64,
+}
+
+/// Deterministic metrics of a single execution of a solution part, recorded by `cargo time --counts`.
+/// These do not depend on the speed of the machine, so they can be compared across runs in CI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Counts {
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+    /// Instructions executed by the binary when running only this part, counted with cachegrind.
+    /// Includes process startup and reading the input. `None` if valgrind is not installed.
+    pub instructions: Option<u64>,
+}
+
+/// A metric of a solution part that exceeds its stored baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regression {
+    pub day: Day,
+    pub part: u8,
+    pub metric: &'static str,
+    pub baseline: u64,
+    pub current: u64,
+}
+
+impl Display for Regression {
+    #[allow(clippy::cast_precision_loss)]
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let change = (self.current as f64 / self.baseline.max(1) as f64 - 1_f64) * 100_f64;
+        write!(
+            f,
+            "Day {} part {}: {} {} -> {} (+{change:.1}%)",
+            self.day, self.part, self.metric, self.baseline, self.current
+        )
+    }
+}
+
+impl Timing {
+    /// Returns the duration of a single part in nanoseconds, if it was benched.
+    pub fn part_nanos(&self, part: u8) -> Option<u64> {
+        self.part(part).map(|p| p.nanos)
+    }
+
+    pub fn part(&self, part: u8) -> Option<PartTiming> {
+        match part {
+            1 => self.part_1,
+            2 => self.part_2,
+            _ => None,
+        }
+    }
+
+    /// Sum of the durations of all benched parts in nanoseconds.
+    pub fn total_nanos(&self) -> u64 {
+        self.part_nanos(1).unwrap_or_default() + self.part_nanos(2).unwrap_or_default()
+    }
+
+    /// Fills in parts that were not benched in `self` from an `older` timing of the same day.
+    pub fn fill_missing_parts(&self, older: &Timing) -> Timing {
+        let mut timing = self.clone();
+        timing.part_1 = timing.part_1.or(older.part_1);
+        timing.part_2 = timing.part_2.or(older.part_2);
+        timing
+    }
+
+    /// Keeps the memory stats and counts of an `older` timing if none were recorded in `self`
+    /// and the solution did not change in between.
+    pub fn fill_missing_stats(&self, older: &Timing) -> Timing {
+        let mut timing = self.clone();
+
+        if timing.source_hash.is_some() && timing.source_hash == older.source_hash {
+            timing.part_1_memory = timing.part_1_memory.or(older.part_1_memory);
+            timing.part_2_memory = timing.part_2_memory.or(older.part_2_memory);
+            timing.part_1_counts = timing.part_1_counts.or(older.part_1_counts);
+            timing.part_2_counts = timing.part_2_counts.or(older.part_2_counts);
+        }
+
+        timing
+    }
+
+    pub fn part_counts(&self, part: u8) -> Option<Counts> {
+        match part {
+            1 => self.part_1_counts,
+            2 => self.part_2_counts,
+            _ => None,
+        }
+    }
+
+    /// Compares the counts of `self` to a `baseline` timing of the same day.
+    /// A metric regresses if it grew by more than `tolerance_percent`.
+    /// Parts or metrics missing on either side are skipped.
+    pub fn count_regressions(&self, baseline: &Timing, tolerance_percent: f64) -> Vec<Regression> {
+        let mut regressions = vec![];
+
+        for part in 1..=2 {
+            let (Some(current), Some(stored)) =
+                (self.part_counts(part), baseline.part_counts(part))
+            else {
+                continue;
+            };
+
+            let metrics = [
+                (
+                    "allocations",
+                    Some(stored.allocations),
+                    Some(current.allocations),
+                ),
+                (
+                    "allocated bytes",
+                    Some(stored.allocated_bytes),
+                    Some(current.allocated_bytes),
+                ),
+                ("instructions", stored.instructions, current.instructions),
+            ];
+
+            for (metric, stored, current) in metrics {
+                let (Some(baseline), Some(current)) = (stored, current) else {
+                    continue;
+                };
+
+                #[allow(clippy::cast_precision_loss)]
+                let limit = baseline as f64 * (1_f64 + tolerance_percent / 100_f64);
+
+                #[allow(clippy::cast_precision_loss)]
+                if current as f64 > limit {
+                    regressions.push(Regression {
+                        day: self.day,
+                        part,
+                        metric,
+                        baseline,
+                        current,
+                    });
+                }
+            }
+        }
+
+        regressions
+    }
+}
+
+/// Formats a byte count with binary unit prefixes, e.g. `1.5 KiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let mut value = bytes as f64 / 1024_f64;
+    let mut unit = 0;
+    while value >= 1024_f64 && unit < UNITS.len() - 1 {
+        value /= 1024_f64;
+        unit += 1;
+    }
+
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Parses a duration in the `Debug` format of [`std::time::Duration`] (e.g. `74.13µs`) to nanoseconds.
+/// The unit has to be a suffix of the value, so strings like `1ms2` are rejected.
+/// Digits are read as integers, so the unrounded `Debug` output of any duration parses back exactly.
+pub fn parse_duration(s: &str) -> Option<u64> {
+    // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
+    const UNITS: [(&str, u32); 4] = [("ns", 0), ("µs", 3), ("ms", 6), ("s", 9)];
+
+    let s = s.trim();
+    let (value, exponent) = UNITS
+        .iter()
+        .find_map(|(unit, exponent)| Some((s.strip_suffix(unit)?, *exponent)))?;
+
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let is_digits = |digits: &str| digits.bytes().all(|b| b.is_ascii_digit());
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+
+    let mut nanos = integer
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(10_u64.pow(exponent))?;
+
+    // digits of the fraction that are whole nanoseconds, and the one after them for rounding.
+    let mut digits = fraction.bytes().map(|b| u64::from(b - b'0'));
+    for place in (0..exponent).rev() {
+        nanos = nanos.checked_add(digits.next().unwrap_or(0) * 10_u64.pow(place))?;
+    }
+    if digits.next().is_some_and(|digit| digit >= 5) {
+        nanos = nanos.checked_add(1)?;
+    }
+
+    Some(nanos)
+}
+
+/// Reads the schema version of a timings document. Documents without a version predate versioning.
+fn schema_version(json: &JsonValue) -> Result<u32, String> {
+    let version = match json
+        .get::<HashMap<String, JsonValue>>()
+        .ok_or("expected JSON document to be an object.")?
+        .get("version")
+    {
+        None => 1,
+        Some(v) => {
+            let version = *v
+                .get::<f64>()
+                .ok_or("expected `json.version` to be a number.")?;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let version = version as u32;
+            version
+        }
+    };
+
+    if version > TIMINGS_VERSION {
+        return Err(format!(
+            "timings were written by a newer version (schema {version}, supported up to {TIMINGS_VERSION})."
+        ));
+    }
+
+    Ok(version)
 }
 
 /// Represents benchmark times for a set of days.
 /// Can be serialized from / to JSON.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Timings {
     pub data: Vec<Timing>,
+    /// Environment of the latest run merged into these timings. `None` for timings stored by older versions.
+    pub environment: Option<Environment>,
 }
 
 impl Timings {
     /// Dehydrate timings to a JSON file.
     pub fn store_file(&self) -> Result<(), Error> {
         let json = JsonValue::from(self.clone());
-        let mut file = fs::File::create(TIMINGS_FILE_PATH)?;
+        let mut file = fs::File::create(&config::get().timings_file.value)?;
         json.format_to(&mut file)
     }
 
     /// Rehydrate timings from a JSON file. If not present, returns empty timings.
+    /// Files written with an older schema are migrated and written back.
     pub fn read_from_file() -> Self {
-        fs::read_to_string(TIMINGS_FILE_PATH)
-            .map_err(|x| x.to_string())
-            .and_then(Timings::try_from)
-            .unwrap_or_default()
+        let path = &config::get().timings_file.value;
+
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Timings::default();
+        };
+
+        let Ok(json) = JsonValue::from_str(&contents) else {
+            return Timings::default();
+        };
+
+        let timings = match Timings::try_from(&json) {
+            Ok(timings) => timings,
+            Err(e) => {
+                eprintln!("Ignoring stored timings in \"{}\": {e}", path.display());
+                return Timings::default();
+            }
+        };
+
+        if schema_version(&json).is_ok_and(|version| version < TIMINGS_VERSION) {
+            match timings.store_file() {
+                Ok(()) => eprintln!(
+                    "Migrated \"{}\" to schema version {TIMINGS_VERSION}.",
+                    path.display()
+                ),
+                Err(e) => eprintln!("Failed to migrate \"{}\": {e}", path.display()),
+            }
+        }
+
+        timings
     }
 
     /// Merge two sets of timings, overwriting `self` with `other` if present.
@@ -51,13 +338,18 @@ impl Timings {
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
-        Timings { data }
+        data.sort_unstable_by_key(|t| t.day);
+        Timings {
+            data,
+            environment: new.environment.clone().or(self.environment.clone()),
+        }
     }
 
     /// Sum up total duration of timings as millis.
     pub fn total_millis(&self) -> f64 {
-        self.data.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64
+        #[allow(clippy::cast_precision_loss)]
+        let nanos = self.data.iter().map(Timing::total_nanos).sum::<u64>() as f64;
+        nanos / 1_000_000_f64
     }
 
     pub fn is_day_complete(&self, day: Day) -> bool {
@@ -65,6 +357,133 @@ impl Timings {
             .iter()
             .any(|t| t.day == day && t.part_1.is_some() && t.part_2.is_some())
     }
+
+    pub fn get(&self, day: Day) -> Option<&Timing> {
+        self.data.iter().find(|t| t.day == day)
+    }
+
+    /// Days and parts with counts in `self` but none in the `baseline`, so that they cannot be checked.
+    pub fn missing_count_baselines(&self, baseline: &Timings) -> Vec<(Day, u8)> {
+        self.data
+            .iter()
+            .flat_map(|timing| (1..=2).map(move |part| (timing, part)))
+            .filter(|(timing, part)| {
+                timing.part_counts(*part).is_some()
+                    && baseline
+                        .get(timing.day)
+                        .and_then(|stored| stored.part_counts(*part))
+                        .is_none()
+            })
+            .map(|(timing, part)| (timing.day, part))
+            .collect()
+    }
+}
+
+/// A stored benchmark run, see the `bench.keep_history` setting.
+#[derive(Clone, Debug)]
+pub struct HistoricRun {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub timings: Timings,
+}
+
+/// All runs stored with `bench.keep_history`, oldest first.
+/// Can be serialized from / to JSON.
+#[derive(Clone, Debug, Default)]
+pub struct TimingsHistory {
+    pub runs: Vec<HistoricRun>,
+}
+
+impl TimingsHistory {
+    /// Rehydrate the history from a JSON file. If not present, returns an empty history.
+    pub fn read_from_file() -> Self {
+        fs::read_to_string(&config::get().timings_history_file.value)
+            .map_err(|x| x.to_string())
+            .and_then(TimingsHistory::try_from)
+            .unwrap_or_default()
+    }
+
+    /// Append `timings` as a new run to the history file.
+    pub fn append_file(timings: &Timings) -> Result<(), Error> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        let mut history = TimingsHistory::read_from_file();
+        history.runs.push(HistoricRun {
+            timestamp,
+            timings: timings.clone(),
+        });
+
+        let json = JsonValue::from(history);
+        let mut file = fs::File::create(&config::get().timings_history_file.value)?;
+        json.format_to(&mut file)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<TimingsHistory> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: TimingsHistory) -> Self {
+        let runs = value
+            .runs
+            .into_iter()
+            .map(|run| {
+                let JsonValue::Object(mut map) = JsonValue::from(run.timings) else {
+                    unreachable!("timings serialize to an object");
+                };
+                // the version is stored once for the whole history.
+                map.remove("version");
+                map.insert("timestamp".into(), JsonValue::Number(run.timestamp as f64));
+                JsonValue::Object(map)
+            })
+            .collect();
+
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert(
+            "version".into(),
+            JsonValue::Number(f64::from(TIMINGS_VERSION)),
+        );
+        map.insert("runs".into(), JsonValue::Array(runs));
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<String> for TimingsHistory {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+        let version = schema_version(&json)?;
+
+        let runs = json
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected JSON document to be an object.")?
+            .get("runs")
+            .ok_or("expected JSON document to have key `runs`.")?
+            .get::<Vec<JsonValue>>()
+            .ok_or("expected `json.runs` to be an array.")?;
+
+        let runs = runs
+            .iter()
+            .map(|run| {
+                let timestamp = run
+                    .get::<HashMap<String, JsonValue>>()
+                    .and_then(|map| map.get("timestamp"))
+                    .and_then(|v| v.get::<f64>().copied())
+                    .ok_or("Expected run.timestamp to be a number.")?;
+
+                Ok(HistoricRun {
+                    timestamp: timestamp as u64,
+                    timings: Timings::from_json(run, version)?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(TimingsHistory { runs })
+    }
 }
 
 /* -------------------------------------------------------------------------- */
@@ -73,11 +492,19 @@ impl From<Timings> for JsonValue {
     fn from(value: Timings) -> Self {
         let mut map: HashMap<String, JsonValue> = HashMap::new();
 
+        map.insert(
+            "version".into(),
+            JsonValue::Number(f64::from(TIMINGS_VERSION)),
+        );
         map.insert(
             "data".into(),
             JsonValue::Array(value.data.iter().map(JsonValue::from).collect()),
         );
 
+        if let Some(environment) = &value.environment {
+            map.insert("environment".into(), JsonValue::from(environment));
+        }
+
         JsonValue::Object(map)
     }
 }
@@ -87,20 +514,43 @@ impl TryFrom<String> for Timings {
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+        Timings::try_from(&json)
+    }
+}
 
-        let json_data = json
+impl TryFrom<&JsonValue> for Timings {
+    type Error = String;
+
+    fn try_from(json: &JsonValue) -> Result<Self, Self::Error> {
+        Timings::from_json(json, schema_version(json)?)
+    }
+}
+
+impl Timings {
+    /// Parses timings stored with the given schema `version`.
+    fn from_json(json: &JsonValue, version: u32) -> Result<Self, String> {
+        let json = json
             .get::<HashMap<String, JsonValue>>()
-            .ok_or("expected JSON document to be an object.")?
+            .ok_or("expected JSON document to be an object.")?;
+
+        let json_data = json
             .get("data")
             .ok_or("expected JSON document to have key `data`.")?
             .get::<Vec<JsonValue>>()
             .ok_or("expected `json.data` to be an array.")?;
 
+        let environment = match json.get("environment") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(Environment::try_from(v)?),
+        };
+
         Ok(Timings {
             data: json_data
                 .iter()
-                .map(Timing::try_from)
+                .map(|timing| Timing::from_json(timing, version))
                 .collect::<Result<_, _>>()?,
+            environment,
         })
     }
 }
@@ -112,23 +562,39 @@ impl From<&Timing> for JsonValue {
         let mut map: HashMap<String, JsonValue> = HashMap::new();
 
         map.insert("day".into(), JsonValue::String(value.day.to_string()));
-        map.insert("total_nanos".into(), JsonValue::Number(value.total_nanos));
 
-        let part_1 = value.part_1.clone().map(JsonValue::String);
-        let part_2 = value.part_2.clone().map(JsonValue::String);
+        if let Some(source_hash) = &value.source_hash {
+            map.insert("source_hash".into(), JsonValue::String(source_hash.clone()));
+        }
+
+        if let Some(memory) = &value.part_1_memory {
+            map.insert("part_1_memory".into(), JsonValue::from(memory));
+        }
+
+        if let Some(memory) = &value.part_2_memory {
+            map.insert("part_2_memory".into(), JsonValue::from(memory));
+        }
+
+        if let Some(counts) = &value.part_1_counts {
+            map.insert("part_1_counts".into(), JsonValue::from(counts));
+        }
+
+        if let Some(counts) = &value.part_2_counts {
+            map.insert("part_2_counts".into(), JsonValue::from(counts));
+        }
 
         map.insert(
             "part_1".into(),
-            match part_1 {
-                Some(x) => x,
+            match &value.part_1 {
+                Some(x) => JsonValue::from(x),
                 None => JsonValue::Null,
             },
         );
 
         map.insert(
             "part_2".into(),
-            match part_2 {
-                Some(x) => x,
+            match &value.part_2 {
+                Some(x) => JsonValue::from(x),
                 None => JsonValue::Null,
             },
         );
@@ -137,10 +603,9 @@ impl From<&Timing> for JsonValue {
     }
 }
 
-impl TryFrom<&JsonValue> for Timing {
-    type Error = String;
-
-    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+impl Timing {
+    /// Parses a timing stored with the given schema `version`.
+    fn from_json(value: &JsonValue, version: u32) -> Result<Self, String> {
         let json = value
             .get::<HashMap<String, JsonValue>>()
             .ok_or("Expected timing to be a JSON object.")?;
@@ -151,76 +616,337 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|day| Day::from_str(day).ok())
             .ok_or("Expected timing.day to be a Day struct.")?;
 
-        let part_1 = json
-            .get("part_1")
-            .map(|v| if v.is_null() { None } else { v.get::<String>() })
-            .ok_or("Expected timing.part_1 to be null or string.")?;
+        let parse_part = |key: &str| -> Result<Option<PartTiming>, String> {
+            let value = json
+                .get(key)
+                .ok_or(format!("Expected timing.{key} to be present."))?;
+
+            if value.is_null() {
+                return Ok(None);
+            }
+
+            if version == 1 {
+                // version 1 stored the formatted duration and no sample count.
+                let nanos = value
+                    .get::<String>()
+                    .and_then(|s| parse_duration(s))
+                    .ok_or(format!("Expected timing.{key} to be null or a duration."))?;
+                return Ok(Some(PartTiming {
+                    nanos,
+                    samples: None,
+                }));
+            }
+
+            PartTiming::try_from(value).map(Some)
+        };
+
+        let part_1 = parse_part("part_1")?;
+        let part_2 = parse_part("part_2")?;
+
+        // optional, timings stored by older versions do not carry a source hash.
+        let source_hash = match json.get("source_hash") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(
+                v.get::<String>()
+                    .ok_or("Expected timing.source_hash to be null or string.")?
+                    .clone(),
+            ),
+        };
+
+        let part_1_memory = match json.get("part_1_memory") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(MemoryStats::try_from(v)?),
+        };
+
+        let part_2_memory = match json.get("part_2_memory") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(MemoryStats::try_from(v)?),
+        };
 
-        let part_2 = json
-            .get("part_2")
-            .map(|v| if v.is_null() { None } else { v.get::<String>() })
-            .ok_or("Expected timing.part_2 to be null or string.")?;
+        let part_1_counts = match json.get("part_1_counts") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(Counts::try_from(v)?),
+        };
 
-        let total_nanos = json
-            .get("total_nanos")
-            .and_then(|v| v.get::<f64>().copied())
-            .ok_or("Expected timing.total_nanos to be a number.")?;
+        let part_2_counts = match json.get("part_2_counts") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(Counts::try_from(v)?),
+        };
 
         Ok(Timing {
             day,
-            part_1: part_1.cloned(),
-            part_2: part_2.cloned(),
-            total_nanos,
+            part_1,
+            part_2,
+            source_hash,
+            part_1_memory,
+            part_2_memory,
+            part_1_counts,
+            part_2_counts,
         })
     }
 }
 
 /* -------------------------------------------------------------------------- */
 
-#[cfg(feature = "test_lib")]
+impl From<&PartTiming> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &PartTiming) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert("nanos".into(), JsonValue::Number(value.nanos as f64));
+        if let Some(samples) = value.samples {
+            map.insert("samples".into(), JsonValue::Number(samples as f64));
+        }
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for PartTiming {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected part timing to be a JSON object.")?;
+
+        let get_number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .map(|v| v as u64)
+        };
+
+        Ok(PartTiming {
+            nanos: get_number("nanos").ok_or("Expected part timing.nanos to be a number.")?,
+            samples: get_number("samples"),
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&MemoryStats> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &MemoryStats) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert(
+            "peak_bytes".into(),
+            JsonValue::Number(value.peak_bytes as f64),
+        );
+        map.insert(
+            "total_bytes".into(),
+            JsonValue::Number(value.total_bytes as f64),
+        );
+        map.insert(
+            "allocations".into(),
+            JsonValue::Number(value.allocations as f64),
+        );
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for MemoryStats {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected memory stats to be a JSON object.")?;
+
+        let get_number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .map(|v| v as u64)
+                .ok_or(format!("Expected memory stats.{key} to be a number."))
+        };
+
+        Ok(MemoryStats {
+            peak_bytes: get_number("peak_bytes")?,
+            total_bytes: get_number("total_bytes")?,
+            allocations: get_number("allocations")?,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&Counts> for JsonValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(value: &Counts) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert(
+            "allocations".into(),
+            JsonValue::Number(value.allocations as f64),
+        );
+        map.insert(
+            "allocated_bytes".into(),
+            JsonValue::Number(value.allocated_bytes as f64),
+        );
+        if let Some(instructions) = value.instructions {
+            map.insert(
+                "instructions".into(),
+                JsonValue::Number(instructions as f64),
+            );
+        }
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for Counts {
+    type Error = String;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected counts to be a JSON object.")?;
+
+        let get_number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .map(|v| v as u64)
+        };
+
+        Ok(Counts {
+            allocations: get_number("allocations")
+                .ok_or("Expected counts.allocations to be a number.")?,
+            allocated_bytes: get_number("allocated_bytes")
+                .ok_or("Expected counts.allocated_bytes to be a number.")?,
+            instructions: get_number("instructions"),
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(test)]
 mod tests {
     use crate::day;
 
-    use super::{Timing, Timings};
+    use super::{PartTiming, Timing, Timings};
+
+    fn millis(millis: u64) -> Option<PartTiming> {
+        Some(PartTiming {
+            nanos: millis * 1_000_000,
+            samples: Some(10),
+        })
+    }
 
     fn get_mock_timings() -> Timings {
         Timings {
             data: vec![
                 Timing {
                     day: day!(1),
-                    part_1: Some("10ms".into()),
-                    part_2: Some("20ms".into()),
-                    total_nanos: 3e+10,
+                    part_1: millis(10),
+                    part_2: millis(20),
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 },
                 Timing {
                     day: day!(2),
-                    part_1: Some("30ms".into()),
-                    part_2: Some("40ms".into()),
-                    total_nanos: 7e+10,
+                    part_1: millis(30),
+                    part_2: millis(40),
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 },
                 Timing {
                     day: day!(4),
-                    part_1: Some("40ms".into()),
+                    part_1: millis(40),
                     part_2: None,
-                    total_nanos: 4e+10,
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 },
             ],
+            environment: None,
         }
     }
 
     mod deserialization {
-        use crate::{day, template::timings::Timings};
+        use crate::{
+            day,
+            template::timings::{PartTiming, Timings, TimingsHistory},
+        };
+        use tinyjson::JsonValue;
 
         #[test]
         fn handles_json_timings() {
-            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000000 }] }"#.to_string();
+            let json = r#"{ "version": 2, "data": [{ "day": "01", "part_1": { "nanos": 1000000, "samples": 50 }, "part_2": null }] }"#.to_string();
             let timings = Timings::try_from(json).unwrap();
             assert_eq!(timings.data.len(), 1);
             let timing = timings.data.first().unwrap();
             assert_eq!(timing.day, day!(1));
-            assert_eq!(timing.part_1, Some("1ms".to_string()));
+            assert_eq!(
+                timing.part_1,
+                Some(PartTiming {
+                    nanos: 1_000_000,
+                    samples: Some(50)
+                })
+            );
             assert_eq!(timing.part_2, None);
-            assert_eq!(timing.total_nanos, 1_000_000_000_f64);
+            assert_eq!(timing.total_nanos(), 1_000_000);
+        }
+
+        #[test]
+        fn migrates_version_1_timings() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "74.1µs", "part_2": "1.5s", "total_nanos": 1500074100 }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            assert_eq!(timing.part_nanos(1), Some(74_100));
+            assert_eq!(timing.part_nanos(2), Some(1_500_000_000));
+            assert_eq!(timing.part_1.unwrap().samples, None);
+
+            let json = JsonValue::from(timings).stringify().unwrap();
+            let timings = Timings::try_from(json).unwrap();
+            assert_eq!(timings.data[0].part_nanos(1), Some(74_100));
+        }
+
+        #[test]
+        #[should_panic]
+        fn panics_for_newer_versions() {
+            let json = r#"{ "version": 99, "data": [] }"#.to_string();
+            Timings::try_from(json).unwrap();
+        }
+
+        #[test]
+        fn handles_optional_fields() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000000, "source_hash": "abc", "part_1_memory": { "peak_bytes": 1024, "total_bytes": 4096, "allocations": 3 } }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            assert_eq!(timing.source_hash, Some("abc".to_string()));
+            assert_eq!(timing.part_1_memory.unwrap().peak_bytes, 1024);
+            assert_eq!(timing.part_1_memory.unwrap().allocations, 3);
+            assert_eq!(timing.part_2_memory, None);
+        }
+
+        #[test]
+        fn handles_history() {
+            let json = r#"{ "runs": [{ "timestamp": 1734000000, "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000 }] }, { "timestamp": 1734100000, "data": [] }] }"#.to_string();
+            let history = TimingsHistory::try_from(json).unwrap();
+            assert_eq!(history.runs.len(), 2);
+            assert_eq!(history.runs[0].timestamp, 1_734_000_000);
+            assert_eq!(history.runs[0].timings.data[0].day, day!(1));
+
+            let json = JsonValue::from(history).stringify().unwrap();
+            let history = TimingsHistory::try_from(json).unwrap();
+            assert_eq!(history.runs[1].timestamp, 1_734_100_000);
+            assert_eq!(
+                history.runs[0].timings.data[0].part_nanos(1),
+                Some(1_000_000)
+            );
         }
 
         #[test]
@@ -245,6 +971,26 @@ mod tests {
         }
     }
 
+    mod parse_duration {
+        use crate::template::timings::parse_duration;
+
+        #[test]
+        fn parses_all_units() {
+            assert_eq!(parse_duration("74ns"), Some(74));
+            assert_eq!(parse_duration("74.13µs"), Some(74_130));
+            assert_eq!(parse_duration("1.5ms"), Some(1_500_000));
+            assert_eq!(parse_duration(" 2s "), Some(2_000_000_000));
+        }
+
+        #[test]
+        fn rejects_malformed_durations() {
+            assert_eq!(parse_duration("1ms2"), None);
+            assert_eq!(parse_duration("ms"), None);
+            assert_eq!(parse_duration("-1s"), None);
+            assert_eq!(parse_duration("1h"), None);
+        }
+    }
+
     mod serialization {
         use super::get_mock_timings;
         use std::collections::HashMap;
@@ -253,7 +999,7 @@ mod tests {
         #[test]
         fn serializes_timings() {
             let timings = get_mock_timings();
-            let value = JsonValue::try_from(timings).unwrap();
+            let value = JsonValue::from(timings);
             assert_eq!(
                 value
                     .get::<HashMap<String, JsonValue>>()
@@ -274,18 +1020,25 @@ mod tests {
             template::timings::{Timing, Timings},
         };
 
+        use super::millis;
+
         #[test]
         fn handles_completed_days() {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
-                    part_1: Some("1ms".into()),
-                    part_2: Some("2ms".into()),
-                    total_nanos: 3_000_000_000_f64,
+                    part_1: millis(1),
+                    part_2: millis(2),
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 }],
+                environment: None,
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), true);
+            assert!(timings.is_day_complete(day!(1)));
         }
 
         #[test]
@@ -293,13 +1046,18 @@ mod tests {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
-                    part_1: Some("1ms".into()),
+                    part_1: millis(1),
                     part_2: None,
-                    total_nanos: 1_000_000_000_f64,
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 }],
+                environment: None,
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert!(!timings.is_day_complete(day!(1)));
         }
 
         #[test]
@@ -309,11 +1067,16 @@ mod tests {
                     day: day!(1),
                     part_1: None,
                     part_2: None,
-                    total_nanos: 0.0,
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 }],
+                environment: None,
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert!(!timings.is_day_complete(day!(1)));
         }
     }
 
@@ -333,8 +1096,13 @@ mod tests {
                     day: day!(3),
                     part_1: None,
                     part_2: None,
-                    total_nanos: 0_f64,
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 }],
+                environment: None,
             };
             let merged = timings.merge(&other);
             assert_eq!(merged.data.len(), 4);
@@ -353,15 +1121,20 @@ mod tests {
                     day: day!(2),
                     part_1: None,
                     part_2: None,
-                    total_nanos: 0_f64,
+                    source_hash: None,
+                    part_1_memory: None,
+                    part_2_memory: None,
+                    part_1_counts: None,
+                    part_2_counts: None,
                 }],
+                environment: None,
             };
             let merged = timings.merge(&other);
 
             assert_eq!(merged.data.len(), 3);
             assert_eq!(merged.data[0].day, day!(1));
             assert_eq!(merged.data[1].day, day!(2));
-            assert_eq!(merged.data[1].total_nanos, 0_f64);
+            assert_eq!(merged.data[1].total_nanos(), 0);
             assert_eq!(merged.data[2].day, day!(4));
         }
 
@@ -381,4 +1154,239 @@ mod tests {
             assert_eq!(merged.data.len(), 3);
         }
     }
+
+    mod count_regressions {
+        use crate::{
+            day,
+            template::timings::{Counts, Timing, Timings},
+        };
+
+        fn timing(part_1: Option<Counts>, part_2: Option<Counts>) -> Timing {
+            Timing {
+                day: day!(1),
+                part_1: None,
+                part_2: None,
+                source_hash: None,
+                part_1_memory: None,
+                part_2_memory: None,
+                part_1_counts: part_1,
+                part_2_counts: part_2,
+            }
+        }
+
+        fn counts(allocations: u64, instructions: Option<u64>) -> Option<Counts> {
+            Some(Counts {
+                allocations,
+                allocated_bytes: 1024,
+                instructions,
+            })
+        }
+
+        #[test]
+        fn detects_regressions_past_tolerance() {
+            let baseline = timing(counts(100, Some(1000)), counts(10, None));
+            let current = timing(counts(101, Some(1011)), counts(11, None));
+
+            let regressions = current.count_regressions(&baseline, 1.0);
+            assert_eq!(regressions.len(), 2);
+            assert_eq!(regressions[0].part, 1);
+            assert_eq!(regressions[0].metric, "instructions");
+            assert_eq!(regressions[1].part, 2);
+            assert_eq!(regressions[1].metric, "allocations");
+            assert_eq!(
+                regressions[1].to_string(),
+                "Day 01 part 2: allocations 10 -> 11 (+10.0%)"
+            );
+        }
+
+        #[test]
+        fn reports_parts_without_baseline() {
+            let baseline = Timings {
+                data: vec![timing(counts(100, None), None)],
+                environment: None,
+            };
+            let current = Timings {
+                data: vec![
+                    timing(counts(100, None), counts(10, None)),
+                    Timing {
+                        day: day!(2),
+                        ..timing(counts(5, None), None)
+                    },
+                ],
+                environment: None,
+            };
+
+            assert_eq!(
+                current.missing_count_baselines(&baseline),
+                vec![(day!(1), 2), (day!(2), 1)]
+            );
+            assert_eq!(
+                current.missing_count_baselines(&Timings::default()).len(),
+                3
+            );
+            assert!(Timings::default()
+                .missing_count_baselines(&baseline)
+                .is_empty());
+        }
+
+        #[test]
+        fn skips_missing_metrics() {
+            let baseline = timing(counts(100, None), None);
+            let current = timing(counts(50, Some(1000)), counts(10, Some(10)));
+            assert!(current.count_regressions(&baseline, 0.0).is_empty());
+        }
+    }
+
+    mod properties {
+        use std::time::Duration;
+
+        use proptest::{collection::vec, option, prelude::*, sample::subsequence};
+        use tinyjson::JsonValue;
+
+        use crate::template::{
+            environment::Environment,
+            timings::{parse_duration, Counts, MemoryStats, PartTiming, Timing, Timings},
+            Day,
+        };
+
+        /// JSON numbers are doubles, so larger integers are not stored exactly.
+        const MAX_JSON_INTEGER: u64 = 1 << 53;
+
+        fn number() -> impl Strategy<Value = u64> {
+            0..=MAX_JSON_INTEGER
+        }
+
+        fn part_timing() -> impl Strategy<Value = PartTiming> {
+            (number(), option::of(number()))
+                .prop_map(|(nanos, samples)| PartTiming { nanos, samples })
+        }
+
+        fn memory_stats() -> impl Strategy<Value = MemoryStats> {
+            (number(), number(), number()).prop_map(|(peak_bytes, total_bytes, allocations)| {
+                MemoryStats {
+                    peak_bytes,
+                    total_bytes,
+                    allocations,
+                }
+            })
+        }
+
+        fn counts() -> impl Strategy<Value = Counts> {
+            (number(), number(), option::of(number())).prop_map(
+                |(allocations, allocated_bytes, instructions)| Counts {
+                    allocations,
+                    allocated_bytes,
+                    instructions,
+                },
+            )
+        }
+
+        fn timing(day: u8) -> impl Strategy<Value = Timing> {
+            (
+                (option::of(part_timing()), option::of(part_timing())),
+                option::of("[0-9a-f]{16}"),
+                (option::of(memory_stats()), option::of(memory_stats())),
+                (option::of(counts()), option::of(counts())),
+            )
+                .prop_map(
+                    move |(
+                        (part_1, part_2),
+                        source_hash,
+                        (part_1_memory, part_2_memory),
+                        (part_1_counts, part_2_counts),
+                    )| Timing {
+                        day: Day::new(day).unwrap(),
+                        part_1,
+                        part_2,
+                        source_hash,
+                        part_1_memory,
+                        part_2_memory,
+                        part_1_counts,
+                        part_2_counts,
+                    },
+                )
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        fn environment() -> impl Strategy<Value = Environment> {
+            (
+                option::of(any::<String>()),
+                option::of(0..=MAX_JSON_INTEGER as usize),
+                option::of(any::<String>()),
+                any::<String>(),
+                vec(any::<String>(), 0..4),
+                vec(any::<String>(), 0..4),
+                option::of("[0-9a-f]{40}"),
+                any::<bool>(),
+            )
+                .prop_map(
+                    |(
+                        cpu_model,
+                        cores,
+                        rustc,
+                        profile,
+                        target_flags,
+                        features,
+                        git_commit,
+                        git_dirty,
+                    )| Environment {
+                        cpu_model,
+                        cores,
+                        rustc,
+                        profile,
+                        target_flags,
+                        features,
+                        git_commit,
+                        git_dirty,
+                    },
+                )
+        }
+
+        /// Timings of distinct days, sorted by day like stored timings.
+        fn timings() -> impl Strategy<Value = Timings> {
+            (
+                subsequence((1..=25).collect::<Vec<u8>>(), 0..=25)
+                    .prop_flat_map(|days| days.into_iter().map(timing).collect::<Vec<_>>()),
+                option::of(environment()),
+            )
+                .prop_map(|(data, environment)| Timings { data, environment })
+        }
+
+        /// Timings of distinct days in any order, like the timings of a single run.
+        fn unsorted_timings() -> impl Strategy<Value = Timings> {
+            timings().prop_flat_map(|timings| {
+                (Just(timings.data).prop_shuffle(), Just(timings.environment))
+                    .prop_map(|(data, environment)| Timings { data, environment })
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn json_round_trips(timings in timings()) {
+                let json = JsonValue::from(timings.clone()).stringify().unwrap();
+                prop_assert_eq!(Timings::try_from(json).unwrap(), timings);
+            }
+
+            #[test]
+            fn merge_is_associative(
+                a in unsorted_timings(),
+                b in unsorted_timings(),
+                c in unsorted_timings(),
+            ) {
+                prop_assert_eq!(a.merge(&b).merge(&c), a.merge(&b.merge(&c)));
+            }
+
+            #[test]
+            fn merge_is_idempotent(timings in timings()) {
+                prop_assert_eq!(timings.merge(&timings), timings.clone());
+                prop_assert_eq!(timings.merge(&Timings::default()), timings);
+            }
+
+            #[test]
+            fn parse_duration_inverts_debug(nanos in any::<u64>()) {
+                let formatted = format!("{:?}", Duration::from_nanos(nanos));
+                prop_assert_eq!(parse_duration(&formatted), Some(nanos));
+            }
+        }
+    }
 }